@@ -1,13 +1,104 @@
 import { NextResponse } from "next/server";
+import { prisma } from "@/lib/db";
 import { getPublicDisclosure } from "@/lib/disclosures";
+import { authorizeOwner, proofItemRows, receiptRows, type PublishBody } from "@/lib/publish";
+import { validateManifest } from "@/lib/validation/manifest";
 
-export async function GET(
-  _request: Request,
-  { params }: { params: { slug: string } }
-) {
+type Params = { params: { slug: string } };
+
+export async function GET(_request: Request, { params }: Params) {
   const disclosure = await getPublicDisclosure(params.slug);
   if (!disclosure) {
     return NextResponse.json({ ok: false, error: "Not found" }, { status: 404 });
   }
   return NextResponse.json(disclosure);
 }
+
+/**
+ * Replaces a publication in place. The slug stays the same and the version
+ * being replaced is kept as a revision.
+ */
+export async function PUT(request: Request, { params }: Params) {
+  try {
+    const existing = await prisma.disclosure.findUnique({ where: { slug: params.slug } });
+    if (!existing) {
+      return NextResponse.json({ ok: false, error: "Not found" }, { status: 404 });
+    }
+    const auth = authorizeOwner(request, existing.ownerTokenHash);
+    if (!auth.ok) {
+      return NextResponse.json({ ok: false, error: auth.error }, { status: auth.status });
+    }
+
+    const body = await request.json();
+    const { manifest, hashes, receipts } = body as PublishBody;
+
+    const validation = validateManifest(manifest);
+    if (!validation.ok) {
+      return NextResponse.json({ ok: false, errors: validation.errors }, { status: 400 });
+    }
+
+    if (!hashes?.manifest_sha256 || !hashes?.bundle_root_sha256) {
+      return NextResponse.json({ ok: false, error: "Invalid hashes payload" }, { status: 400 });
+    }
+
+    const previous = (existing.manifest as any)?.publication || {};
+    const revision = existing.revision + 1;
+    const url = previous.url;
+
+    const manifestToStore = {
+      ...manifest,
+      publication: {
+        slug: existing.slug,
+        url,
+        published_at: previous.published_at,
+        updated_at: new Date().toISOString(),
+        revision
+      }
+    };
+
+    await prisma.$transaction([
+      prisma.disclosureRevision.create({
+        data: {
+          disclosureId: existing.id,
+          revision: existing.revision,
+          manifest: existing.manifest as any,
+          hashes: existing.hashes as any
+        }
+      }),
+      prisma.proofItem.deleteMany({ where: { disclosureId: existing.id } }),
+      prisma.otsReceipt.deleteMany({ where: { disclosureId: existing.id } }),
+      prisma.disclosure.update({
+        where: { id: existing.id },
+        data: {
+          manifest: manifestToStore,
+          hashes,
+          revision,
+          proofItems: { create: proofItemRows(manifest) },
+          receipts: { create: receiptRows(receipts) }
+        }
+      })
+    ]);
+
+    return NextResponse.json({ slug: existing.slug, url, revision });
+  } catch (error) {
+    return NextResponse.json({ ok: false, error: (error as Error).message }, { status: 500 });
+  }
+}
+
+export async function DELETE(request: Request, { params }: Params) {
+  try {
+    const existing = await prisma.disclosure.findUnique({ where: { slug: params.slug } });
+    if (!existing) {
+      return NextResponse.json({ ok: false, error: "Not found" }, { status: 404 });
+    }
+    const auth = authorizeOwner(request, existing.ownerTokenHash);
+    if (!auth.ok) {
+      return NextResponse.json({ ok: false, error: auth.error }, { status: auth.status });
+    }
+
+    await prisma.disclosure.delete({ where: { id: existing.id } });
+    return NextResponse.json({ ok: true, slug: existing.slug });
+  } catch (error) {
+    return NextResponse.json({ ok: false, error: (error as Error).message }, { status: 500 });
+  }
+}
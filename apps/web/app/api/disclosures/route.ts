@@ -1,23 +1,15 @@
 import { NextResponse } from "next/server";
 import { nanoid } from "nanoid";
-import crypto from "node:crypto";
 import { prisma } from "@/lib/db";
+import { bearerTokenHash, proofItemRows, receiptRows, type PublishBody } from "@/lib/publish";
 import { validateManifest } from "@/lib/validation/manifest";
 
 const BASE_URL = process.env.NEXT_PUBLIC_BASE_URL;
 
-function sha256Hex(data: Uint8Array) {
-  return crypto.createHash("sha256").update(data).digest("hex");
-}
-
 export async function POST(request: Request) {
   try {
     const body = await request.json();
-    const { manifest, hashes, receipts } = body as {
-      manifest: any;
-      hashes: any;
-      receipts?: Array<{ filename: string; bytes_base64: string }>;
-    };
+    const { manifest, hashes, receipts } = body as PublishBody;
 
     const validation = validateManifest(manifest);
     if (!validation.ok) {
@@ -32,45 +24,31 @@ export async function POST(request: Request) {
     const slug = nanoid(8);
     const publishedAt = new Date().toISOString();
     const url = `${origin}/d/${slug}`;
+    const revision = 1;
 
     const manifestToStore = {
       ...manifest,
       publication: {
         slug,
         url,
-        published_at: publishedAt
+        published_at: publishedAt,
+        revision
       }
     };
 
-    const receiptData = (receipts || []).map((receipt) => {
-      const bytes = Buffer.from(receipt.bytes_base64, "base64");
-      return {
-        filename: receipt.filename,
-        bytes,
-        sha256: sha256Hex(bytes)
-      };
-    });
+    const proofItems = proofItemRows(manifest);
+    const receiptData = receiptRows(receipts);
 
     await prisma.disclosure.create({
       data: {
         slug,
         manifest: manifestToStore,
         hashes,
-        proofItems: manifest.proof.items.length
+        revision,
+        ownerTokenHash: bearerTokenHash(request),
+        proofItems: proofItems.length
           ? {
-              create: manifest.proof.items.map((item: any) => ({
-                proofId: item.id,
-                label: item.label,
-                kind: item.kind,
-                path: item.path,
-                mime: item.mime,
-                sizeBytes: item.size_bytes,
-                sha256: item.sha256,
-                createdBeforeAi: item.created_before_ai,
-                notes: item.notes,
-                gitRepo: item.git?.repo,
-                gitCommit: item.git?.commit
-              }))
+              create: proofItems
             }
           : undefined,
         receipts: receiptData.length
@@ -81,7 +59,7 @@ export async function POST(request: Request) {
       }
     });
 
-    return NextResponse.json({ slug, url });
+    return NextResponse.json({ slug, url, revision });
   } catch (error) {
     return NextResponse.json({ ok: false, error: (error as Error).message }, { status: 500 });
   }
@@ -45,7 +45,12 @@ export default function DisclosurePreview({ manifest, hashes }: DisclosurePrevie
         </div>
         <div>
           <p className="text-xs uppercase tracking-widest text-muted">Proof items</p>
-          <p className="font-semibold">{manifest.proof.items.length}</p>
+          <p className="font-semibold">
+            {manifest.proof.items.length}
+            {manifest.proof.completeness?.slots.length
+              ? ` - ${manifest.proof.completeness.score_percent}% of recommended proof`
+              : ""}
+          </p>
         </div>
         <div>
           <p className="text-xs uppercase tracking-widest text-muted">Bundle root</p>
@@ -17,8 +17,30 @@ function manifestForHash(manifest: DisclosureManifest): DisclosureManifest {
   return clone;
 }
 
+const HASHED_PATH_PREFIX = "sha256:";
+
+// Mirrors PathPolicy::apply in the CLI: paths are hashed the way
+// proof.path_policy publishes them. Already redacted paths stay as they are.
+export async function applyPathPolicy(
+  path: string,
+  policy: DisclosureManifest["proof"]["path_policy"]
+): Promise<string> {
+  if (policy === "basename") {
+    return path.split(/[\\/]/).pop() ?? path;
+  }
+  if (policy === "hash" && !path.startsWith(HASHED_PATH_PREFIX)) {
+    return HASHED_PATH_PREFIX + (await sha256Hex(path.replace(/\\/g, "/")));
+  }
+  return path;
+}
+
 export async function computeManifestHash(manifest: DisclosureManifest): Promise<string> {
   const sanitized = manifestForHash(manifest);
+  for (const item of sanitized.proof?.items ?? []) {
+    if (item.path !== undefined) {
+      item.path = await applyPathPolicy(item.path, sanitized.proof.path_policy);
+    }
+  }
   return sha256Hex(stableStringify(sanitized));
 }
 
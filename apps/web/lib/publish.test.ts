@@ -0,0 +1,17 @@
+import { expect, test } from "vitest";
+import { authorizeOwner, bearerTokenHash, sha256Hex } from "@/lib/publish";
+
+function request(authorization?: string) {
+  return new Request("http://localhost/api/disclosures/abc", {
+    headers: authorization ? { authorization } : {}
+  });
+}
+
+test("only the publishing token may change a disclosure", () => {
+  const owner = bearerTokenHash(request("Bearer secret"));
+  expect(owner).toBe(sha256Hex("secret"));
+  expect(authorizeOwner(request("Bearer secret"), owner)).toEqual({ ok: true });
+  expect(authorizeOwner(request("Bearer other"), owner)).toMatchObject({ status: 403 });
+  expect(authorizeOwner(request(), owner)).toMatchObject({ status: 401 });
+  expect(authorizeOwner(request("Bearer secret"), null)).toMatchObject({ status: 403 });
+});
@@ -0,0 +1,60 @@
+import crypto from "node:crypto";
+
+export type PublishBody = {
+  manifest: any;
+  hashes: any;
+  receipts?: Array<{ filename: string; bytes_base64: string }>;
+};
+
+export function sha256Hex(data: Uint8Array | string) {
+  return crypto.createHash("sha256").update(data).digest("hex");
+}
+
+/**
+ * Hash of the bearer token sent with a request. Only the hash is stored, as
+ * the owner of a publication.
+ */
+export function bearerTokenHash(request: Request): string | null {
+  const header = request.headers.get("authorization") || "";
+  const match = header.match(/^Bearer\s+(.+)$/i);
+  return match ? sha256Hex(match[1].trim()) : null;
+}
+
+/** Checks the bearer token against the owner of a publication. */
+export function authorizeOwner(request: Request, ownerTokenHash: string | null) {
+  const tokenHash = bearerTokenHash(request);
+  if (!tokenHash) {
+    return { ok: false as const, status: 401, error: "Missing bearer token" };
+  }
+  if (!ownerTokenHash || tokenHash !== ownerTokenHash) {
+    return { ok: false as const, status: 403, error: "Token does not own this disclosure" };
+  }
+  return { ok: true as const };
+}
+
+export function proofItemRows(manifest: any) {
+  return manifest.proof.items.map((item: any) => ({
+    proofId: item.id,
+    label: item.label,
+    kind: item.kind,
+    path: item.path,
+    mime: item.mime,
+    sizeBytes: item.size_bytes,
+    sha256: item.sha256,
+    createdBeforeAi: item.created_before_ai,
+    notes: item.notes,
+    gitRepo: item.git?.repo,
+    gitCommit: item.git?.commit
+  }));
+}
+
+export function receiptRows(receipts: PublishBody["receipts"]) {
+  return (receipts || []).map((receipt) => {
+    const bytes = Buffer.from(receipt.bytes_base64, "base64");
+    return {
+      filename: receipt.filename,
+      bytes,
+      sha256: sha256Hex(bytes)
+    };
+  });
+}
@@ -2,6 +2,15 @@ export type TemplateStage = {
   key: string;
   label: string;
   description?: string;
+  weight?: number;
+};
+
+export type GradeLevel = {
+  key: string;
+  label: string;
+  min_percent: number;
+  max_percent: number;
+  percent?: number;
 };
 
 export type Template = {
@@ -10,6 +19,7 @@ export type Template = {
   label: string;
   recommended_proof?: string[];
   stages: TemplateStage[];
+  grades?: GradeLevel[];
 };
 
 export type AssistanceGrade = "none" | "light" | "moderate" | "heavy" | "full";
@@ -18,7 +28,9 @@ export type AiTool = {
   name: string;
   provider?: string;
   model?: string;
+  version?: string;
   notes?: string;
+  stages?: string[];
 };
 
 export type ProofKind = "file" | "git_commit" | "text_note";
@@ -89,8 +101,27 @@ export type DisclosureManifest = {
     stages?: Array<{
       key: string;
       label: string;
-      grade: AssistanceGrade;
+      // A key from the template's grade scale; the default scale is AssistanceGrade.
+      grade: AssistanceGrade | string;
       approx_ai_percent: number;
+      ai_percent_range?: {
+        min: number;
+        max: number;
+      };
+    }>;
+    scoped?: Array<{
+      scope: {
+        kind: "proof" | "glob" | "component";
+        value: string;
+      };
+      human_percent: number;
+      ai_percent: number;
+      grade?: string;
+      ai_percent_range?: {
+        min: number;
+        max: number;
+      };
+      notes?: string;
     }>;
     notes?: string;
   };
@@ -109,8 +140,21 @@ export type DisclosureManifest = {
         repo: string;
         commit: string;
       };
+      slot?: string;
     }>;
+    // How item paths were redacted; absent means "relative". Hashed paths
+    // read "sha256:<hex of the relative path>".
+    path_policy?: "relative" | "basename" | "hash";
     bundle_root_sha256?: string;
+    completeness?: {
+      score_percent: number;
+      slots: Array<{
+        key: string;
+        label: string;
+        optional?: boolean;
+        proof_ids: string[];
+      }>;
+    };
   };
   timestamps?: {
     opentimestamps?: {
@@ -124,6 +168,9 @@ export type DisclosureManifest = {
     slug?: string;
     url?: string;
     published_at?: string;
+    updated_at?: string;
+    revision?: number;
+    target?: "api" | "static" | "s3";
   };
 };
 
@@ -0,0 +1,21 @@
+-- AlterTable
+ALTER TABLE "Disclosure" ADD COLUMN     "ownerTokenHash" TEXT,
+ADD COLUMN     "revision" INTEGER NOT NULL DEFAULT 1;
+
+-- CreateTable
+CREATE TABLE "DisclosureRevision" (
+    "id" TEXT NOT NULL,
+    "disclosureId" TEXT NOT NULL,
+    "revision" INTEGER NOT NULL,
+    "manifest" JSONB NOT NULL,
+    "hashes" JSONB NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "DisclosureRevision_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "DisclosureRevision_disclosureId_revision_key" ON "DisclosureRevision"("disclosureId", "revision");
+
+-- AddForeignKey
+ALTER TABLE "DisclosureRevision" ADD CONSTRAINT "DisclosureRevision_disclosureId_fkey" FOREIGN KEY ("disclosureId") REFERENCES "Disclosure"("id") ON DELETE CASCADE ON UPDATE CASCADE;
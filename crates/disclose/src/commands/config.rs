@@ -0,0 +1,94 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::{ExportFormat, IncludeProof};
+use crate::config::{config_key, write_config_value, Config, ConfigValue, CONFIG_KEYS};
+use crate::errors::ValidationError;
+use crate::publish::TargetKind;
+
+fn invalid(key: &str, value: &str, expected: &str) -> anyhow::Error {
+    ValidationError::new(format!(
+        "Invalid value {} for {}, expected {}",
+        value, key, expected
+    ))
+    .into()
+}
+
+/// Checks a value the way the matching flag would and returns it typed for
+/// the TOML file.
+fn config_value(key: &str, value: &str) -> Result<toml::Value> {
+    match key {
+        "export.include_proof" => {
+            value
+                .parse::<IncludeProof>()
+                .map_err(|_| invalid(key, value, "none|hashes|copies"))?;
+        }
+        "export.format" => {
+            value
+                .parse::<ExportFormat>()
+                .map_err(|_| invalid(key, value, "zip|dir"))?;
+        }
+        "publish.target" => {
+            value
+                .parse::<TargetKind>()
+                .map_err(|_| invalid(key, value, "api|static|s3"))?;
+        }
+        "export.include_receipts" | "publish.include_receipts"
+            if !matches!(value, "yes" | "no" | "true" | "false") =>
+        {
+            return Err(invalid(key, value, "yes|no"));
+        }
+        "stamp.timeout" | "publish.timeout" => {
+            let seconds = value
+                .parse::<i64>()
+                .ok()
+                .filter(|seconds| *seconds > 0)
+                .ok_or_else(|| invalid(key, value, "a number of seconds"))?;
+            return Ok(toml::Value::Integer(seconds));
+        }
+        "publish.retries" => {
+            let retries = value
+                .parse::<u32>()
+                .map_err(|_| invalid(key, value, "a number of retries"))?;
+            return Ok(toml::Value::Integer(retries.into()));
+        }
+        _ if value.trim().is_empty() => return Err(invalid(key, value, "a non-empty value")),
+        _ => {}
+    }
+    Ok(toml::Value::String(value.to_string()))
+}
+
+pub fn set_config(path: &Path, key: &str, value: &str) -> Result<()> {
+    config_key(key)?;
+    write_config_value(path, key, Some(config_value(key, value)?))
+}
+
+pub fn unset_config(path: &Path, key: &str) -> Result<()> {
+    config_key(key)?;
+    write_config_value(path, key, None)
+}
+
+pub fn get_config(config: &Config, key: &str) -> Result<Option<ConfigValue>> {
+    config_key(key)?;
+    Ok(config.entry(key).cloned())
+}
+
+pub fn format_config_value(entry: &ConfigValue) -> String {
+    format!("{} = {}  [{}]", entry.key, entry.value, entry.source)
+}
+
+pub fn format_config_list(config: &Config) -> String {
+    let mut out = String::new();
+    for known in CONFIG_KEYS {
+        match config.entry(known.key) {
+            Some(entry) => {
+                let _ = writeln!(out, "{}", format_config_value(entry));
+            }
+            None => {
+                let _ = writeln!(out, "{} (unset) - {}", known.key, known.description);
+            }
+        }
+    }
+    out.trim_end().to_string()
+}
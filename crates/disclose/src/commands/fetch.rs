@@ -0,0 +1,362 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ValidationError;
+use crate::hashing::{build_stored_hashes, sha256_hex_bytes, HashesJson};
+use crate::manifest::DisclosureManifest;
+use crate::ots;
+use crate::publish::{fetch_disclosure, HttpOptions};
+use crate::workspace::{Workspace, FETCHED_STATUS};
+
+/// A value recomputed locally next to the value the server or the manifest
+/// claims for it.
+#[derive(Debug, Clone, Serialize)]
+pub struct HashCheck {
+    pub name: String,
+    pub claimed: String,
+    pub computed: String,
+    pub matches: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptCheck {
+    pub filename: String,
+    pub sha256: String,
+    /// Result of verifying the receipt against the recomputed bundle root;
+    /// `None` when the verifier could not run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchReport {
+    pub slug: String,
+    pub url: String,
+    pub workspace: PathBuf,
+    pub manifest_sha256: String,
+    pub bundle_root_sha256: String,
+    pub checks: Vec<HashCheck>,
+    pub receipts: Vec<ReceiptCheck>,
+}
+
+impl FetchReport {
+    /// Names of checks that failed, including receipts that did not verify.
+    pub fn mismatches(&self) -> Vec<String> {
+        let mut mismatches: Vec<String> = self
+            .checks
+            .iter()
+            .filter(|check| !check.matches)
+            .map(|check| check.name.clone())
+            .collect();
+        mismatches.extend(
+            self.receipts
+                .iter()
+                .filter(|receipt| receipt.verified == Some(false))
+                .map(|receipt| format!("receipt {}", receipt.filename)),
+        );
+        mismatches
+    }
+}
+
+/// Splits a publication URL (`https://host/d/<slug>`) into its origin and
+/// slug. Anything without a scheme is taken as a bare slug.
+pub fn parse_fetch_source(source: &str) -> Result<(Option<String>, String)> {
+    let source = source.trim().trim_end_matches('/');
+    let Some((scheme, rest)) = source.split_once("://") else {
+        if source.is_empty() || source.contains('/') {
+            return Err(ValidationError::new(format!("Invalid slug {}", source)).into());
+        }
+        return Ok((None, source.to_string()));
+    };
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let slug = path
+        .rsplit('/')
+        .next()
+        .filter(|slug| !slug.is_empty())
+        .ok_or_else(|| ValidationError::new(format!("No slug in {}", source)))?;
+    Ok((Some(format!("{}://{}", scheme, host)), slug.to_string()))
+}
+
+fn check(name: impl Into<String>, claimed: &str, computed: &str) -> HashCheck {
+    HashCheck {
+        name: name.into(),
+        claimed: claimed.to_string(),
+        computed: computed.to_string(),
+        matches: claimed.eq_ignore_ascii_case(computed),
+    }
+}
+
+/// Compares what the server claims in `hashes.json` and what the manifest
+/// claims about itself with values recomputed from the manifest.
+fn hash_checks(
+    manifest: &DisclosureManifest,
+    claimed: &HashesJson,
+    computed: &HashesJson,
+) -> Vec<HashCheck> {
+    let mut checks = vec![
+        check(
+            "manifest_sha256 (hashes.json)",
+            &claimed.manifest_sha256,
+            &computed.manifest_sha256,
+        ),
+        check(
+            "bundle_root_sha256 (hashes.json)",
+            &claimed.bundle_root_sha256,
+            &computed.bundle_root_sha256,
+        ),
+    ];
+    if let Some(root) = &manifest.proof.bundle_root_sha256 {
+        checks.push(check(
+            "bundle_root_sha256 (disclosure.json)",
+            root,
+            &computed.bundle_root_sha256,
+        ));
+    }
+    for item in &manifest.proof.items {
+        let served = claimed
+            .proof
+            .iter()
+            .find(|entry| entry.id == item.id)
+            .map(|entry| entry.sha256.as_str())
+            .unwrap_or("(missing)");
+        checks.push(check(
+            format!("proof {} (hashes.json)", item.id),
+            served,
+            &item.sha256,
+        ));
+    }
+    for entry in &claimed.proof {
+        if !manifest.proof.items.iter().any(|item| item.id == entry.id) {
+            checks.push(check(
+                format!("proof {} (hashes.json)", entry.id),
+                &entry.sha256,
+                "(not in manifest)",
+            ));
+        }
+    }
+    checks
+}
+
+/// Compares the receipt hash recorded in `timestamps.opentimestamps` with
+/// the receipt the server returned under that name.
+fn receipt_hash_check(
+    manifest: &DisclosureManifest,
+    receipts: &[ReceiptCheck],
+) -> Option<HashCheck> {
+    let ots = manifest.timestamps.as_ref()?.opentimestamps.as_ref()?;
+    let (claimed, filename) = (ots.receipt_sha256.as_ref()?, ots.receipt_filename.as_ref()?);
+    let served = receipts
+        .iter()
+        .find(|receipt| &receipt.filename == filename)
+        .map(|receipt| receipt.sha256.as_str())
+        .unwrap_or("(receipt not served)");
+    Some(check(
+        format!("receipt {} sha256 (disclosure.json)", filename),
+        claimed,
+        served,
+    ))
+}
+
+/// Clears a previous fetch of the same publication. Anything else in the
+/// folder is left alone and makes the fetch fail instead.
+fn prepare_out_dir(workspace: &Workspace) -> Result<()> {
+    if !workspace.exists() {
+        return Ok(());
+    }
+    if workspace.fetched_from().is_none() {
+        return Err(ValidationError::new(format!(
+            "{} already holds a disclosure. Choose another --out",
+            workspace.root_path().display()
+        ))
+        .into());
+    }
+    // Removing read-only files only needs write access to the folder.
+    fs::remove_file(workspace.disclosure_path())?;
+    if workspace.hashes_path().exists() {
+        fs::remove_file(workspace.hashes_path())?;
+    }
+    if workspace.receipts_dir().exists() {
+        fs::remove_dir_all(workspace.receipts_dir())?;
+    }
+    Ok(())
+}
+
+fn write_read_only(path: &Path, bytes: &[u8]) -> Result<()> {
+    fs::write(path, bytes)?;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+/// Downloads a publication into a read-only workspace at `out` and checks it
+/// independently: the manifest hash and bundle root are recomputed and
+/// compared with the served `hashes.json` and the manifest's own claims, and
+/// every receipt is verified against the recomputed bundle root.
+pub async fn fetch_workspace(
+    endpoint: &str,
+    slug: &str,
+    out: &Path,
+    timeout: Option<u64>,
+    http: HttpOptions,
+) -> Result<FetchReport> {
+    let fetched = fetch_disclosure(endpoint, slug, http).await?;
+    let manifest = DisclosureManifest::from_value(fetched.manifest.clone())?;
+    let claimed: HashesJson = serde_json::from_value(fetched.hashes.clone())
+        .map_err(|err| anyhow!("Server returned invalid hashes: {}", err))?;
+    let computed = build_stored_hashes(&fetched.manifest, &manifest)?;
+
+    let workspace = Workspace::new(out.to_path_buf());
+    prepare_out_dir(&workspace)?;
+    fs::create_dir_all(workspace.receipts_dir())?;
+    write_read_only(
+        &workspace.disclosure_path(),
+        serde_json::to_string_pretty(&fetched.manifest)?.as_bytes(),
+    )?;
+    write_read_only(
+        &workspace.hashes_path(),
+        serde_json::to_string_pretty(&fetched.hashes)?.as_bytes(),
+    )?;
+
+    let mut checks = hash_checks(&manifest, &claimed, &computed);
+    let mut receipts = Vec::new();
+    for receipt in &fetched.receipts {
+        // Never let a served filename point outside the receipts folder.
+        let Some(filename) = Path::new(&receipt.filename).file_name() else {
+            continue;
+        };
+        let filename = filename.to_string_lossy().to_string();
+        let bytes = general_purpose::STANDARD
+            .decode(&receipt.bytes_base64)
+            .map_err(|err| anyhow!("Invalid receipt {}: {}", filename, err))?;
+        let path = workspace.receipts_dir().join(&filename);
+        write_read_only(&path, &bytes)?;
+        let sha256 = sha256_hex_bytes(&bytes);
+        let (verified, error) = match ots::verify(&path, &computed.bundle_root_sha256, timeout) {
+            Ok(verified) => (Some(verified), None),
+            Err(err) => (None, Some(err.to_string().trim().to_string())),
+        };
+        receipts.push(ReceiptCheck {
+            filename,
+            sha256,
+            verified,
+            error,
+        });
+    }
+    checks.extend(receipt_hash_check(&manifest, &receipts));
+
+    let url = manifest
+        .publication
+        .as_ref()
+        .and_then(|publication| publication.url.clone())
+        .unwrap_or_else(|| format!("{}/d/{}", endpoint.trim_end_matches('/'), slug));
+    workspace.ensure_state_dir()?;
+    let state = json!({
+        "version": 1,
+        "status": FETCHED_STATUS,
+        "source": {
+            "url": url,
+            "endpoint": endpoint,
+            "slug": slug,
+            "fetched_at": Utc::now().to_rfc3339()
+        }
+    });
+    fs::write(
+        workspace.state_path(),
+        serde_json::to_string_pretty(&state)?,
+    )?;
+
+    Ok(FetchReport {
+        slug: slug.to_string(),
+        url,
+        workspace: workspace.root,
+        manifest_sha256: computed.manifest_sha256,
+        bundle_root_sha256: computed.bundle_root_sha256,
+        checks,
+        receipts,
+    })
+}
+
+pub fn format_fetch_report(report: &FetchReport) -> String {
+    let mut out = format!(
+        "Fetched {} into {} (read-only)\n  manifest_sha256:    {}\n  bundle_root_sha256: {}\n",
+        report.url,
+        report.workspace.display(),
+        report.manifest_sha256,
+        report.bundle_root_sha256
+    );
+    for check in &report.checks {
+        if check.matches {
+            let _ = writeln!(out, "  ok       {}", check.name);
+        } else {
+            let _ = writeln!(
+                out,
+                "  MISMATCH {}: claimed {}, computed {}",
+                check.name, check.claimed, check.computed
+            );
+        }
+    }
+    if report.receipts.is_empty() {
+        let _ = writeln!(out, "  no receipts served");
+    }
+    for receipt in &report.receipts {
+        let _ = match (receipt.verified, &receipt.error) {
+            (Some(true), _) => writeln!(out, "  ok       receipt {} verifies", receipt.filename),
+            (Some(false), _) => writeln!(
+                out,
+                "  MISMATCH receipt {} does not verify against the bundle root",
+                receipt.filename
+            ),
+            (None, error) => writeln!(
+                out,
+                "  ?        receipt {} could not be verified: {}",
+                receipt.filename,
+                error.as_deref().unwrap_or("unknown error")
+            ),
+        };
+    }
+    let mismatches = report.mismatches();
+    if mismatches.is_empty() {
+        out.push_str("Everything the server returned matches what the manifest claims");
+    } else {
+        let _ = write!(out, "{} mismatch(es) found", mismatches.len());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_are_urls_or_slugs() {
+        assert_eq!(
+            parse_fetch_source("https://disclose.aislop.sh/d/abc123/").unwrap(),
+            (
+                Some("https://disclose.aislop.sh".to_string()),
+                "abc123".to_string()
+            )
+        );
+        assert_eq!(
+            parse_fetch_source("http://localhost:3000/api/disclosures/abc123?x=1").unwrap(),
+            (
+                Some("http://localhost:3000".to_string()),
+                "abc123".to_string()
+            )
+        );
+        assert_eq!(
+            parse_fetch_source("abc123").unwrap(),
+            (None, "abc123".to_string())
+        );
+        assert!(parse_fetch_source("https://disclose.aislop.sh").is_err());
+    }
+}
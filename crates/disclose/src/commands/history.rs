@@ -0,0 +1,74 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::load_template_from_manifest;
+use crate::errors::ValidationError;
+use crate::git_history::{analyze_commits, read_git_log, GitAnalysis, GIT_STAGES, NOTES_PREFIX};
+use crate::manifest::DisclosureManifest;
+use crate::workspace::Workspace;
+
+/// Reads the commit history of `repo` and suggests a split and stage grades
+/// for templates with `implementation`, `testing` or `docs` stages.
+pub fn analyze_git_history(workspace: &Workspace, repo: &Path) -> Result<GitAnalysis> {
+    let manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let template = load_template_from_manifest(workspace, &manifest)?;
+    if !template
+        .stages
+        .iter()
+        .any(|stage| GIT_STAGES.contains(&stage.key.as_str()))
+    {
+        return Err(ValidationError::new(format!(
+            "--from-git needs a template with {} stages; {} has none",
+            GIT_STAGES.join(", "),
+            template.slug
+        ))
+        .into());
+    }
+    let commits = read_git_log(repo)?;
+    if commits.is_empty() {
+        return Err(ValidationError::new(format!("No commits found in {}", repo.display())).into());
+    }
+    Ok(analyze_commits(&template, &commits))
+}
+
+/// Stores the analysis summary in `assistance.notes`, replacing the summary
+/// of an earlier run and keeping any other notes.
+pub(super) fn record_git_analysis(manifest: &mut DisclosureManifest, analysis: &GitAnalysis) {
+    let mut paragraphs: Vec<String> = manifest
+        .assistance
+        .notes
+        .as_deref()
+        .unwrap_or_default()
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty() && !paragraph.starts_with(NOTES_PREFIX))
+        .map(str::to_string)
+        .collect();
+    paragraphs.push(analysis.summary());
+    manifest.assistance.notes = Some(paragraphs.join("\n\n"));
+}
+
+/// The `meter --from-git` proposal: the history summary, then the split and
+/// stage grades that would be written. Callers say how to accept it.
+pub fn format_git_proposal(
+    analysis: &GitAnalysis,
+    global_ai: Option<i32>,
+    stages: &[(String, String)],
+) -> String {
+    let mut out = analysis.summary();
+    if let Some(ai) = global_ai {
+        let _ = write!(
+            out,
+            "\n\nProposed global split: {}% human, {}% AI",
+            100 - ai,
+            ai
+        );
+    }
+    if !stages.is_empty() {
+        out.push_str("\n\nProposed stage grades:");
+        for (key, grade) in stages {
+            let _ = write!(out, "\n  {}: {}", key, grade);
+        }
+    }
+    out
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+
+use crate::hashing::{build_hashes, manifest_hash_value, write_hashes};
+use crate::manifest::DisclosureManifest;
+use crate::migrations::migrate_value;
+use crate::validation::validate_manifest;
+use crate::workspace::Workspace;
+
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    pub from_version: String,
+    pub to_version: String,
+    pub applied: Vec<String>,
+    pub manifest_hash_changed: bool,
+    pub receipt_invalidated: bool,
+    pub publication_stale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+}
+
+/// Rewrites `disclosure.json` at the latest manifest version. The previous file
+/// is kept under `.disclose/` so the stamped bytes are never lost.
+pub fn migrate_workspace(workspace: &Workspace) -> Result<MigrationReport> {
+    workspace.ensure_writable()?;
+    let stored = DisclosureManifest::read_value(&workspace.disclosure_path())?;
+    let old_hash = manifest_hash_value(&stored)?;
+
+    let mut value = stored.clone();
+    let outcome = migrate_value(&mut value)?;
+    if !outcome.changed() {
+        return Ok(MigrationReport {
+            from_version: outcome.from_version,
+            to_version: outcome.to_version,
+            applied: Vec::new(),
+            manifest_hash_changed: false,
+            receipt_invalidated: false,
+            publication_stale: false,
+            backup: None,
+        });
+    }
+
+    let mut manifest: DisclosureManifest = serde_json::from_value(value)?;
+    let hashes = build_hashes(&manifest)?;
+    let manifest_hash_changed = hashes.manifest_sha256 != old_hash;
+    manifest.proof.bundle_root_sha256 = Some(hashes.bundle_root_sha256.clone());
+    validate_manifest(&manifest)?;
+
+    workspace.ensure_state_dir()?;
+    let backup = workspace
+        .state_dir()
+        .join(format!("disclosure.v{}.json", outcome.from_version));
+    fs::write(&backup, serde_json::to_string_pretty(&stored)?)?;
+
+    manifest.write_to(&workspace.disclosure_path())?;
+    write_hashes(&workspace.hashes_path(), &hashes)?;
+
+    Ok(MigrationReport {
+        from_version: outcome.from_version,
+        to_version: outcome.to_version,
+        applied: outcome.applied,
+        manifest_hash_changed,
+        receipt_invalidated: manifest_hash_changed && manifest.has_receipt(),
+        publication_stale: manifest_hash_changed && manifest.publication.is_some(),
+        backup: Some(backup.to_string_lossy().to_string()),
+    })
+}
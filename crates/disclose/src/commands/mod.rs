@@ -1,25 +1,60 @@
 use anyhow::{anyhow, Result};
-use base64::{engine::general_purpose, Engine as _};
 use chrono::Utc;
 use mime_guess::MimeGuess;
+use serde::Serialize;
 use serde_json::json;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::completeness::{compute_completeness, resolve_slot};
 use crate::errors::ValidationError;
-use crate::hashing::{build_hashes, hash_file, write_hashes, HashesJson};
+use crate::git_history::GitAnalysis;
+use crate::hashing::{build_hashes, build_stored_hashes, hash_file, write_hashes, HashesJson};
 use crate::manifest::{
-    AssistanceGrade, AssistanceInfo, AssistanceStage, DisclosureManifest, OpenTimestampsInfo,
-    ProjectInfo, ProofInfo, ProofItem, ProofKind, TemplateRef, TimestampInfo,
+    AssistanceInfo, AssistanceScope, DisclosureManifest, OpenTimestampsInfo, PathPolicy,
+    ProjectInfo, ProofCompleteness, ProofInfo, ProofItem, ProofKind, TemplateRef, TimestampInfo,
 };
+use crate::migrations::{migrate_value, CURRENT_MANIFEST_VERSION};
 use crate::ots;
-use crate::publish::{publish_disclosure, ReceiptPayload};
-use crate::templates::{get_template, Template};
-use crate::validation::validate_manifest;
+use crate::pii::{scan_file, scan_manifest, Finding};
+use crate::templates::{Template, TemplateCatalog};
+use crate::validation::{validate_manifest, validate_stage_grades};
 use crate::workspace::Workspace;
 
+mod config;
+mod fetch;
+mod history;
+mod migrate;
+mod project;
+mod publish;
+mod scopes;
+mod status;
+mod templates;
+mod tools;
+mod usage;
+
+pub use config::{format_config_list, format_config_value, get_config, set_config, unset_config};
+pub use fetch::{fetch_workspace, format_fetch_report, parse_fetch_source};
+pub use history::{analyze_git_history, format_git_proposal};
+pub use migrate::migrate_workspace;
+pub use project::{format_project_report, set_project, ProjectUpdate};
+pub use publish::{
+    format_publish_plan, plan_publish, publish_workspace, send_publish_plan, unpublish_workspace,
+};
+pub use scopes::{parse_scope, ScopeChange, ScopeUpdate};
+pub use status::{format_checklist, format_status, workspace_status};
+pub use templates::{
+    format_template, format_template_lint, format_template_list, format_template_upgrade,
+    lint_template, list_templates, show_template, upgrade_template,
+};
+pub use tools::{add_tool, format_tool_list, list_tools, remove_tool};
+pub use usage::{format_usage_import, import_usage};
+
+use history::record_git_analysis;
+use scopes::{remove_scoped_assistance, set_scoped_assistance};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IncludeProof {
     None,
@@ -58,68 +93,206 @@ impl std::str::FromStr for ExportFormat {
     }
 }
 
+/// How `meter` reconciles the declared global split with the stage grades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitMode {
+    Warn,
+    Reject,
+    Derive,
+}
+
+impl std::str::FromStr for SplitMode {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "warn" => Ok(SplitMode::Warn),
+            "reject" => Ok(SplitMode::Reject),
+            "derive" => Ok(SplitMode::Derive),
+            _ => Err(anyhow!("Invalid split mode")),
+        }
+    }
+}
+
+pub const DEFAULT_SPLIT_TOLERANCE: i32 = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitCheck {
+    pub declared_ai_percent: i32,
+    pub derived_ai_percent: i32,
+    pub tolerance: i32,
+    pub within_tolerance: bool,
+}
+
+impl SplitCheck {
+    pub fn message(&self) -> String {
+        format!(
+            "Global split declares {}% AI but the weighted stage grades suggest ~{}% AI (tolerance {} points)",
+            self.declared_ai_percent, self.derived_ai_percent, self.tolerance
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct MeterReport {
+    pub hashes: HashesJson,
+    pub split: Option<SplitCheck>,
+}
+
+/// Split and stage changes for [`update_meter`] and [`meter_workspace`].
+#[derive(Debug, Clone)]
+pub struct MeterChanges {
+    pub global_human: Option<i32>,
+    pub global_ai: Option<i32>,
+    pub stages: Vec<(String, String)>,
+    pub allow_unknown: bool,
+    pub split_mode: SplitMode,
+    pub tolerance: i32,
+}
+
+#[derive(Debug)]
+pub struct InitReport {
+    pub workspace: Workspace,
+    /// Where the previous manifest went when re-initializing with `force`.
+    pub backup: Option<PathBuf>,
+    pub imported_from: Option<PathBuf>,
+}
+
+/// Creates a workspace in `out_dir`, which may already exist as long as it
+/// has no `disclosure.json`. With `force` an existing manifest is moved to
+/// `.disclose/backup-<timestamp>/` first. `import_from` seeds the project,
+/// tools and assistance from another manifest; explicit arguments win.
+#[allow(clippy::too_many_arguments)]
 pub fn init_workspace(
     out_dir: PathBuf,
-    template_slug: &str,
-    title: &str,
+    template_slug: Option<&str>,
+    template_file: Option<&Path>,
+    title: Option<&str>,
     author: Option<String>,
     links: Vec<String>,
-) -> Result<Workspace> {
-    if out_dir.exists() {
-        return Err(anyhow!("Output directory already exists"));
+    force: bool,
+    import_from: Option<&Path>,
+) -> Result<InitReport> {
+    if out_dir.exists() && !out_dir.is_dir() {
+        return Err(anyhow!("Output path exists and is not a directory"));
     }
-
-    fs::create_dir_all(&out_dir)?;
     let workspace = Workspace::new(out_dir);
+    if workspace.exists() && !force {
+        return Err(ValidationError::new(format!(
+            "{} already exists; use --force to re-initialize (the old manifest is backed up)",
+            workspace.disclosure_path().display()
+        ))
+        .into());
+    }
+    let source = match import_from {
+        Some(path) => Some(DisclosureManifest::read_from(path).map_err(|err| {
+            ValidationError::new(format!("Cannot import {}: {}", path.display(), err))
+        })?),
+        None => None,
+    };
+
+    // Later commands resolve `<workspace>/templates` too, so init must as well.
+    let mut catalog = TemplateCatalog::discover(Some(workspace.root_path()))?;
+    let file_template = match template_file {
+        Some(path) => Some(catalog.add_file(path)?),
+        None => None,
+    };
+    let template = match (template_slug, &file_template, &source) {
+        (Some(slug), Some(file), _) if slug != file.slug => {
+            return Err(ValidationError::new(format!(
+                "--template {} does not match the slug {} in --template-file",
+                slug, file.slug
+            ))
+            .into())
+        }
+        (_, Some(file), _) => file.clone(),
+        (Some(slug), None, _) => catalog.get(slug)?,
+        (None, None, Some(source)) => catalog
+            .get_version(&source.template.slug, &source.template.version)
+            .or_else(|_| catalog.get(&source.template.slug))?,
+        (None, None, None) => return Err(anyhow!("Provide --template or --template-file")),
+    };
+    let title = title
+        .map(str::to_string)
+        .or_else(|| source.as_ref().map(|source| source.project.title.clone()))
+        .ok_or_else(|| ValidationError::new("Provide --title"))?;
+
+    fs::create_dir_all(workspace.root_path())?;
     workspace.ensure_state_dir()?;
+    let backup = if workspace.exists() {
+        Some(backup_manifest(&workspace)?)
+    } else {
+        None
+    };
 
-    let template = get_template(template_slug)?;
+    // Keep custom templates next to the manifest so later commands resolve
+    // the same stages without needing the original file.
+    if let Some(path) = template_file {
+        fs::create_dir_all(workspace.templates_dir())?;
+        fs::copy(
+            path,
+            workspace
+                .templates_dir()
+                .join(format!("{}.json", template.slug)),
+        )?;
+    }
 
     let stages = template
         .stages
         .iter()
-        .map(|stage| AssistanceStage {
-            key: stage.key.clone(),
-            label: stage.label.clone(),
-            grade: AssistanceGrade::None,
-            approx_ai_percent: 0,
-        })
+        .map(|stage| template.lowest_grade().stage(&stage.key, &stage.label))
         .collect();
 
-    let manifest = DisclosureManifest {
-        version: "1.0.0".to_string(),
+    let mut manifest = DisclosureManifest {
+        version: CURRENT_MANIFEST_VERSION.to_string(),
         id: format!("dsc_{}", Uuid::new_v4()),
         created_at: Utc::now().to_rfc3339(),
         template: TemplateRef {
-            slug: template.slug,
-            version: template.version,
+            slug: template.slug.clone(),
+            version: template.version.clone(),
+            extra: Default::default(),
         },
         project: ProjectInfo {
-            title: title.to_string(),
-            author,
-            links: if links.is_empty() { None } else { Some(links) },
+            title: String::new(),
+            author: None,
+            links: None,
             audience: Some("public".to_string()),
+            extra: Default::default(),
         },
         ai_tools: None,
         assistance: AssistanceInfo {
             global: crate::manifest::AssistanceGlobal {
                 human_percent: 70,
                 ai_percent: 30,
+                extra: Default::default(),
             },
             stages: Some(stages),
+            scoped: None,
             notes: None,
+            extra: Default::default(),
         },
         proof: ProofInfo {
             items: Vec::new(),
+            path_policy: None,
             bundle_root_sha256: None,
+            completeness: Some(compute_completeness(&template, &[])),
+            extra: Default::default(),
         },
         timestamps: None,
         publication: None,
+        extra: Default::default(),
     };
+    if let Some(source) = source {
+        import_manifest_fields(&mut manifest, source, &template);
+    }
+    manifest.project.title = title;
+    if author.is_some() {
+        manifest.project.author = author;
+    }
+    if !links.is_empty() {
+        manifest.project.links = Some(links);
+    }
 
-    validate_manifest(&manifest)?;
-    validate_manifest(&manifest)?;
-    validate_manifest(&manifest)?;
     validate_manifest(&manifest)?;
     manifest.write_to(&workspace.disclosure_path())?;
 
@@ -132,7 +305,80 @@ pub fn init_workspace(
         serde_json::to_string_pretty(&state)?,
     )?;
 
-    Ok(workspace)
+    Ok(InitReport {
+        workspace,
+        backup,
+        imported_from: import_from.map(Path::to_path_buf),
+    })
+}
+
+/// Moves `disclosure.json`, `hashes.json` and `receipts/` into a timestamped
+/// folder under `.disclose/` so a re-initialized workspace never loses the
+/// stamped bytes or leaves a receipt for the old root behind.
+fn backup_manifest(workspace: &Workspace) -> Result<PathBuf> {
+    let backup = workspace
+        .state_dir()
+        .join(format!("backup-{}", Utc::now().format("%Y%m%dT%H%M%S%.3f")));
+    fs::create_dir_all(&backup)?;
+    for path in [
+        workspace.disclosure_path(),
+        workspace.hashes_path(),
+        workspace.receipts_dir(),
+    ] {
+        if path.exists() {
+            let name = path.file_name().unwrap_or_default();
+            fs::rename(&path, backup.join(name))?;
+        }
+    }
+    Ok(backup)
+}
+
+/// Copies project info, tools and assistance from another manifest. Proof,
+/// timestamps and publication describe the source's own files and are not
+/// carried over; stage grades the template cannot express start at its
+/// lowest grade.
+fn import_manifest_fields(
+    manifest: &mut DisclosureManifest,
+    source: DisclosureManifest,
+    template: &Template,
+) {
+    let known = |key: &str| template.stages.iter().any(|stage| stage.key == key);
+    manifest.project = source.project;
+    manifest.ai_tools = source.ai_tools.map(|tools| {
+        tools
+            .into_iter()
+            .map(|mut tool| {
+                tool.stages = tool
+                    .stages
+                    .map(|stages| stages.into_iter().filter(|key| known(key)).collect())
+                    .filter(|stages: &Vec<String>| !stages.is_empty());
+                tool
+            })
+            .collect()
+    });
+    manifest.assistance.global = source.assistance.global;
+    manifest.assistance.notes = source.assistance.notes;
+    manifest.assistance.scoped = source
+        .assistance
+        .scoped
+        .map(|scoped| {
+            scoped
+                .into_iter()
+                .filter(|entry| !matches!(entry.scope, AssistanceScope::Proof(_)))
+                .collect::<Vec<_>>()
+        })
+        .filter(|scoped| !scoped.is_empty());
+    let imported = source.assistance.stages.unwrap_or_default();
+    for stage in manifest.assistance.stages.iter_mut().flatten() {
+        let Some(previous) = imported.iter().find(|previous| previous.key == stage.key) else {
+            continue;
+        };
+        if let Ok(level) = template.grade(previous.grade.as_str()) {
+            *stage = level.stage(&stage.key, &stage.label);
+            stage.extra = previous.extra.clone();
+        }
+    }
+    manifest.extra = source.extra;
 }
 
 fn build_file_proof(
@@ -181,6 +427,8 @@ fn build_file_proof(
         created_before_ai,
         notes: note.map(|n| n.to_string()),
         git: None,
+        slot: None,
+        extra: Default::default(),
     })
 }
 
@@ -202,10 +450,14 @@ fn build_git_proof(repo: &str, commit: &str, label: Option<&str>, note: Option<&
         git: Some(crate::manifest::GitProof {
             repo: Some(repo.to_string()),
             commit: Some(commit.to_string()),
+            extra: Default::default(),
         }),
+        slot: None,
+        extra: Default::default(),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn attach_proof(
     workspace: &Workspace,
     proof_paths: Vec<PathBuf>,
@@ -214,8 +466,17 @@ pub fn attach_proof(
     created_before_ai: Option<bool>,
     copy_into: Option<PathBuf>,
     git: Option<(String, String)>,
+    slot: Option<String>,
 ) -> Result<HashesJson> {
+    workspace.ensure_writable()?;
     let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let template = load_template_from_manifest(workspace, &manifest);
+    let slot = match (slot, &template) {
+        (Some(slot), Ok(template)) => Some(resolve_slot(template, &slot)?),
+        (Some(_), Err(err)) => return Err(anyhow!("Cannot resolve --slot: {}", err)),
+        (None, _) => None,
+    };
+    let first_new = manifest.proof.items.len();
 
     let copy_dir_opt = copy_into.as_deref();
 
@@ -239,6 +500,14 @@ pub fn attach_proof(
         )?;
         manifest.proof.items.push(proof);
     }
+    for item in &mut manifest.proof.items[first_new..] {
+        item.slot = slot.clone();
+    }
+    // Proof can still be attached when the pinned template is gone; the
+    // checklist is then left as it was.
+    if let Ok(template) = &template {
+        manifest.proof.completeness = Some(compute_completeness(template, &manifest.proof.items));
+    }
 
     let hashes = build_hashes(&manifest)?;
     manifest.proof.bundle_root_sha256 = Some(hashes.bundle_root_sha256.clone());
@@ -248,36 +517,71 @@ pub fn attach_proof(
     Ok(hashes)
 }
 
-fn grade_from_str(value: &str) -> Result<AssistanceGrade> {
-    match value {
-        "none" => Ok(AssistanceGrade::None),
-        "light" => Ok(AssistanceGrade::Light),
-        "moderate" => Ok(AssistanceGrade::Moderate),
-        "heavy" => Ok(AssistanceGrade::Heavy),
-        "full" => Ok(AssistanceGrade::Full),
-        _ => Err(ValidationError::new("Unknown grade").into()),
-    }
-}
-
-fn grade_percent(grade: &AssistanceGrade) -> i32 {
-    match grade {
-        AssistanceGrade::None => 0,
-        AssistanceGrade::Light => 10,
-        AssistanceGrade::Moderate => 30,
-        AssistanceGrade::Heavy => 60,
-        AssistanceGrade::Full => 90,
-    }
-}
-
 pub fn update_meter(
     workspace: &Workspace,
     global_human: Option<i32>,
     global_ai: Option<i32>,
     stages: Vec<(String, String)>,
     allow_unknown: bool,
-) -> Result<HashesJson> {
+    split_mode: SplitMode,
+    tolerance: i32,
+) -> Result<MeterReport> {
+    meter_workspace(
+        workspace,
+        None,
+        Some(MeterChanges {
+            global_human,
+            global_ai,
+            stages,
+            allow_unknown,
+            split_mode,
+            tolerance,
+        }),
+        None,
+    )
+}
+
+/// Applies one `disclose meter` run to a single copy of the manifest and
+/// writes it once, so a rejected change leaves the workspace untouched.
+pub fn meter_workspace(
+    workspace: &Workspace,
+    scope: Option<ScopeChange>,
+    meter: Option<MeterChanges>,
+    analysis: Option<&GitAnalysis>,
+) -> Result<MeterReport> {
+    workspace.ensure_writable()?;
     let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
-    let template = get_template(&manifest.template.slug)?;
+    match scope {
+        Some(ScopeChange::Set(update)) => set_scoped_assistance(workspace, &mut manifest, update)?,
+        Some(ScopeChange::Remove(scope)) => remove_scoped_assistance(&mut manifest, scope)?,
+        None => {}
+    }
+    let split = match meter {
+        Some(changes) => apply_meter(workspace, &mut manifest, changes)?,
+        None => None,
+    };
+    if let Some(analysis) = analysis {
+        record_git_analysis(&mut manifest, analysis);
+    }
+    let hashes = save_manifest(workspace, &mut manifest)?;
+    Ok(MeterReport { hashes, split })
+}
+
+/// Applies split and stage changes to `manifest` without writing it.
+pub(super) fn apply_meter(
+    workspace: &Workspace,
+    manifest: &mut DisclosureManifest,
+    changes: MeterChanges,
+) -> Result<Option<SplitCheck>> {
+    let MeterChanges {
+        global_human,
+        global_ai,
+        stages,
+        allow_unknown,
+        split_mode,
+        tolerance,
+    } = changes;
+    let template = load_template_from_manifest(workspace, manifest)?;
 
     if let Some(human) = global_human {
         let ai = global_ai.unwrap_or(100 - human);
@@ -289,13 +593,9 @@ pub fn update_meter(
     }
 
     if !stages.is_empty() {
-        let mut stage_map = std::collections::HashMap::new();
-        for stage in manifest.assistance.stages.clone().unwrap_or_default() {
-            stage_map.insert(stage.key.clone(), stage);
-        }
+        let mut current = manifest.assistance.stages.take().unwrap_or_default();
         for (key, grade_str) in stages {
-            let grade = grade_from_str(&grade_str)?;
-            let approx = grade_percent(&grade);
+            let level = template.grade(&grade_str)?;
             if !template.stages.iter().any(|stage| stage.key == key) && !allow_unknown {
                 return Err(ValidationError::new("Unknown stage key").into());
             }
@@ -305,25 +605,59 @@ pub fn update_meter(
                 .find(|stage| stage.key == key)
                 .map(|stage| stage.label.clone())
                 .unwrap_or_else(|| key.clone());
-            stage_map.insert(
-                key.clone(),
-                AssistanceStage {
-                    key,
-                    label,
-                    grade,
-                    approx_ai_percent: approx,
-                },
-            );
+            let mut stage = level.stage(&key, &label);
+            match current.iter_mut().find(|existing| existing.key == key) {
+                Some(existing) => {
+                    stage.extra = std::mem::take(&mut existing.extra);
+                    *existing = stage;
+                }
+                None => current.push(stage),
+            }
+        }
+        manifest.assistance.stages = Some(current);
+    }
+    validate_stage_grades(manifest, &template)?;
+
+    let derived =
+        template.weighted_ai_percent(manifest.assistance.stages.as_deref().unwrap_or_default());
+    if split_mode == SplitMode::Derive {
+        let ai = derived.ok_or_else(|| {
+            ValidationError::new("No stage grades to derive the global split from")
+        })?;
+        manifest.assistance.global.ai_percent = ai;
+        manifest.assistance.global.human_percent = 100 - ai;
+    }
+    let split = derived.map(|derived| {
+        let declared = manifest.assistance.global.ai_percent;
+        SplitCheck {
+            declared_ai_percent: declared,
+            derived_ai_percent: derived,
+            tolerance,
+            within_tolerance: (declared - derived).abs() <= tolerance,
+        }
+    });
+    if let Some(check) = split.as_ref().filter(|check| !check.within_tolerance) {
+        if split_mode == SplitMode::Reject {
+            return Err(ValidationError::new(check.message()).into());
         }
-        manifest.assistance.stages = Some(stage_map.into_values().collect());
     }
+    Ok(split)
+}
 
-    let hashes = build_hashes(&manifest)?;
-    manifest.proof.bundle_root_sha256 = Some(hashes.bundle_root_sha256.clone());
-    validate_manifest(&manifest)?;
-    manifest.write_to(&workspace.disclosure_path())?;
-    write_hashes(&workspace.hashes_path(), &hashes)?;
-    Ok(hashes)
+/// Export, publish and stamp ship or stamp hashes that must match the
+/// stored `disclosure.json`. A manifest written by an older CLI is only
+/// migrated in memory when read, so they wait for `disclose migrate`.
+pub fn ensure_migrated(workspace: &Workspace) -> Result<()> {
+    let mut value = DisclosureManifest::read_value(&workspace.disclosure_path())?;
+    let outcome = migrate_value(&mut value)?;
+    if outcome.changed() {
+        return Err(ValidationError::new(format!(
+            "disclosure.json is at manifest version {}. Run `disclose migrate` to move it to {} first",
+            outcome.from_version, outcome.to_version
+        ))
+        .into());
+    }
+    Ok(())
 }
 
 pub fn stamp_workspace(
@@ -334,6 +668,8 @@ pub fn stamp_workspace(
     upgrade: bool,
     timeout: Option<u64>,
 ) -> Result<()> {
+    workspace.ensure_writable()?;
+    ensure_migrated(workspace)?;
     let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
     let hashes = build_hashes(&manifest)?;
     let bundle_root = digest.unwrap_or(hashes.bundle_root_sha256);
@@ -353,7 +689,9 @@ pub fn stamp_workspace(
             receipt_filename: receipt_path
                 .file_name()
                 .map(|n| n.to_string_lossy().to_string()),
+            extra: Default::default(),
         }),
+        extra: Default::default(),
     });
 
     if upgrade {
@@ -373,6 +711,7 @@ pub fn stamp_workspace(
 }
 
 pub fn upgrade_receipt(workspace: &Workspace, receipt: Option<PathBuf>) -> Result<bool> {
+    workspace.ensure_writable()?;
     let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
     let receipt_path = resolve_receipt_path(workspace, receipt);
     let changed = ots::upgrade(&receipt_path)?;
@@ -393,8 +732,9 @@ pub fn verify_receipt(
     receipt: Option<PathBuf>,
     timeout: Option<u64>,
 ) -> Result<bool> {
-    let manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
-    let hashes = build_hashes(&manifest)?;
+    let stored = DisclosureManifest::read_value(&workspace.disclosure_path())?;
+    let manifest = DisclosureManifest::from_value(stored.clone())?;
+    let hashes = build_stored_hashes(&stored, &manifest)?;
     let receipt_path = resolve_receipt_path(workspace, receipt);
     ots::verify(&receipt_path, &hashes.bundle_root_sha256, timeout)
 }
@@ -404,14 +744,88 @@ pub fn info_receipt(workspace: &Workspace, receipt: Option<PathBuf>) -> Result<S
     ots::info(&receipt_path)
 }
 
+/// Redacts proof paths for export or publish. `policy` overrides the one
+/// stored in the manifest; a different policy changes the bundle root, so a
+/// stamped manifest has to switch policy and be stamped again instead.
+pub fn apply_path_policy(
+    manifest: &mut DisclosureManifest,
+    policy: Option<PathPolicy>,
+) -> Result<PathPolicy> {
+    let stored = manifest.path_policy();
+    let policy = policy.unwrap_or(stored);
+    if policy != stored && manifest.has_receipt() {
+        return Err(ValidationError::new(format!(
+            "The receipt covers proof paths under the {} path policy. Run `disclose project set --path-policy {}` and stamp again, or leave out --path-policy",
+            stored, policy
+        ))
+        .into());
+    }
+    manifest.redact_paths(policy);
+    if policy != stored {
+        manifest.proof.bundle_root_sha256 = Some(build_hashes(manifest)?.bundle_root_sha256);
+    }
+    Ok(policy)
+}
+
+/// Scans what an export would contain for personal data and secrets: the
+/// manifest's text fields after path redaction and, with `copies`, the proof
+/// files themselves.
+pub fn scan_export(
+    workspace: &Workspace,
+    include_proof: IncludeProof,
+    path_policy: Option<PathPolicy>,
+) -> Result<Vec<Finding>> {
+    let local = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let mut manifest = local.clone();
+    apply_path_policy(&mut manifest, path_policy)?;
+    let mut findings = scan_manifest(&manifest)?;
+    if include_proof == IncludeProof::Copies {
+        for path in local
+            .proof
+            .items
+            .iter()
+            .filter_map(|item| item.path.as_ref())
+        {
+            let src = workspace.root_path().join(path);
+            if src.is_file() {
+                findings.extend(scan_file(&src, path)?);
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Proof files to copy into a bundle, with their name under `proof/`.
+fn proof_copies(
+    workspace: &Workspace,
+    manifest: &DisclosureManifest,
+    policy: PathPolicy,
+) -> Vec<(PathBuf, String)> {
+    manifest
+        .proof
+        .items
+        .iter()
+        .filter_map(|item| item.path.as_ref())
+        .map(|path| (workspace.root_path().join(path), policy.bundle_name(path)))
+        .filter(|(src, _)| src.exists())
+        .collect()
+}
+
+/// Writes the bundle. Callers run [`scan_export`] first and settle its
+/// findings with the user, so the files are scanned only once.
 pub fn export_bundle(
     workspace: &Workspace,
     bundle_path: PathBuf,
     include_proof: IncludeProof,
     include_receipts: bool,
     format: ExportFormat,
-) -> Result<()> {
-    let manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    path_policy: Option<PathPolicy>,
+) -> Result<Option<ProofCompleteness>> {
+    ensure_migrated(workspace)?;
+    let local = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let mut manifest = local.clone();
+    let policy = apply_path_policy(&mut manifest, path_policy)?;
+    let copies = proof_copies(workspace, &local, policy);
     let hashes = build_hashes(&manifest)?;
     let receipt_dir = workspace.receipts_dir();
 
@@ -442,17 +856,8 @@ pub fn export_bundle(
             if include_proof == IncludeProof::Copies {
                 let dest = bundle_path.join("proof");
                 fs::create_dir_all(&dest)?;
-                for item in manifest.proof.items.iter() {
-                    if let Some(path) = &item.path {
-                        let src = workspace.root_path().join(path);
-                        if src.exists() {
-                            let filename = src
-                                .file_name()
-                                .map(|n| n.to_string_lossy().to_string())
-                                .unwrap_or_else(|| "proof".to_string());
-                            fs::copy(&src, dest.join(filename))?;
-                        }
-                    }
+                for (src, name) in &copies {
+                    fs::copy(src, dest.join(name))?;
                 }
             }
         }
@@ -483,19 +888,10 @@ pub fn export_bundle(
             }
 
             if include_proof == IncludeProof::Copies {
-                for item in manifest.proof.items.iter() {
-                    if let Some(path) = &item.path {
-                        let src = workspace.root_path().join(path);
-                        if src.exists() {
-                            let name = format!(
-                                "proof/{}",
-                                src.file_name().unwrap_or_default().to_string_lossy()
-                            );
-                            zip.start_file(name, options)?;
-                            let bytes = fs::read(src)?;
-                            zip.write_all(&bytes)?;
-                        }
-                    }
+                for (src, name) in &copies {
+                    zip.start_file(format!("proof/{}", name), options)?;
+                    let bytes = fs::read(src)?;
+                    zip.write_all(&bytes)?;
                 }
             }
 
@@ -503,58 +899,37 @@ pub fn export_bundle(
         }
     }
 
-    Ok(())
+    Ok(manifest.proof.completeness)
 }
 
-pub async fn publish_workspace(
+pub fn load_template_from_manifest(
     workspace: &Workspace,
-    endpoint: &str,
-    token: Option<String>,
-    include_receipts: bool,
-) -> Result<(String, String)> {
-    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
-    let hashes = build_hashes(&manifest)?;
-
-    let receipts = if include_receipts {
-        let mut receipts = Vec::new();
-        if workspace.receipts_dir().exists() {
-            for entry in fs::read_dir(workspace.receipts_dir())? {
-                let entry = entry?;
-                let bytes = fs::read(entry.path())?;
-                receipts.push(ReceiptPayload {
-                    filename: entry.file_name().to_string_lossy().to_string(),
-                    bytes_base64: general_purpose::STANDARD.encode(bytes),
-                });
-            }
-        }
-        receipts
-    } else {
-        Vec::new()
-    };
-
-    let response =
-        publish_disclosure(endpoint, token.as_deref(), &manifest, &hashes, receipts).await?;
-    manifest.publication = Some(crate::manifest::PublicationInfo {
-        slug: Some(response.slug.clone()),
-        url: Some(response.url.clone()),
-        published_at: Some(Utc::now().to_rfc3339()),
-    });
-    manifest.write_to(&workspace.disclosure_path())?;
-    Ok((response.slug, response.url))
-}
-
-#[allow(dead_code)]
-pub fn load_template_from_manifest(manifest: &DisclosureManifest) -> Result<Template> {
-    get_template(&manifest.template.slug)
+    manifest: &DisclosureManifest,
+) -> Result<Template> {
+    TemplateCatalog::discover(Some(workspace.root_path()))?
+        .get_version(&manifest.template.slug, &manifest.template.version)
 }
 
-#[allow(dead_code)]
 pub fn recompute_root(manifest: &mut DisclosureManifest) -> Result<HashesJson> {
     let hashes = build_hashes(manifest)?;
     manifest.proof.bundle_root_sha256 = Some(hashes.bundle_root_sha256.clone());
     Ok(hashes)
 }
 
+/// Recomputes hashes, validates and writes both `disclosure.json` and
+/// `hashes.json`.
+pub fn save_manifest(
+    workspace: &Workspace,
+    manifest: &mut DisclosureManifest,
+) -> Result<HashesJson> {
+    workspace.ensure_writable()?;
+    let hashes = recompute_root(manifest)?;
+    validate_manifest(manifest)?;
+    manifest.write_to(&workspace.disclosure_path())?;
+    write_hashes(&workspace.hashes_path(), &hashes)?;
+    Ok(hashes)
+}
+
 fn resolve_receipt_path(workspace: &Workspace, receipt: Option<PathBuf>) -> PathBuf {
     match receipt {
         Some(path) if path.is_absolute() => path,
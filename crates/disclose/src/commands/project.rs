@@ -0,0 +1,146 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+use super::save_manifest;
+use crate::errors::ValidationError;
+use crate::hashing::manifest_hash_value;
+use crate::manifest::{DisclosureManifest, PathPolicy, ProjectInfo};
+use crate::workspace::Workspace;
+
+/// Values accepted for `project.audience`, as in the disclosure schema.
+pub const AUDIENCES: &[&str] = &["public", "employer", "school", "publisher", "private"];
+
+/// Changes requested by `disclose project set`. An empty `author` or
+/// `assistance_notes` clears the field.
+#[derive(Debug, Default)]
+pub struct ProjectUpdate {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub add_links: Vec<String>,
+    pub remove_links: Vec<String>,
+    pub audience: Option<String>,
+    pub assistance_notes: Option<String>,
+    pub path_policy: Option<PathPolicy>,
+}
+
+impl ProjectUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.author.is_none()
+            && self.add_links.is_empty()
+            && self.remove_links.is_empty()
+            && self.audience.is_none()
+            && self.assistance_notes.is_none()
+            && self.path_policy.is_none()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectReport {
+    pub project: ProjectInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assistance_notes: Option<String>,
+    pub path_policy: PathPolicy,
+    pub bundle_root: String,
+    pub manifest_hash_changed: bool,
+    pub receipt_invalidated: bool,
+    pub publication_stale: bool,
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+pub fn set_project(workspace: &Workspace, update: ProjectUpdate) -> Result<ProjectReport> {
+    if update.is_empty() {
+        return Err(ValidationError::new("Nothing to change").into());
+    }
+    let stored = DisclosureManifest::read_value(&workspace.disclosure_path())?;
+    let old_hash = manifest_hash_value(&stored)?;
+    let mut manifest = DisclosureManifest::from_value(stored)?;
+    let project = &mut manifest.project;
+
+    if let Some(title) = update.title {
+        project.title = non_empty(title)
+            .ok_or_else(|| ValidationError::new("Project title must not be empty"))?;
+    }
+    if let Some(author) = update.author {
+        project.author = non_empty(author);
+    }
+    if let Some(audience) = update.audience {
+        if !AUDIENCES.contains(&audience.as_str()) {
+            return Err(ValidationError::new(format!(
+                "Unknown audience {}. Expected one of: {}",
+                audience,
+                AUDIENCES.join(", ")
+            ))
+            .into());
+        }
+        project.audience = Some(audience);
+    }
+    let mut links = project.links.take().unwrap_or_default();
+    for link in update.remove_links {
+        let before = links.len();
+        links.retain(|existing| existing != link.trim());
+        if links.len() == before {
+            return Err(ValidationError::new(format!("No project link {}", link)).into());
+        }
+    }
+    for link in update.add_links {
+        let link = non_empty(link)
+            .ok_or_else(|| ValidationError::new("Project link must not be empty"))?;
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    project.links = (!links.is_empty()).then_some(links);
+    if let Some(notes) = update.assistance_notes {
+        manifest.assistance.notes = non_empty(notes);
+    }
+    if let Some(policy) = update.path_policy {
+        manifest.proof.path_policy = Some(policy);
+    }
+
+    let hashes = save_manifest(workspace, &mut manifest)?;
+    let manifest_hash_changed = hashes.manifest_sha256 != old_hash;
+    Ok(ProjectReport {
+        receipt_invalidated: manifest_hash_changed && manifest.has_receipt(),
+        path_policy: manifest.path_policy(),
+        publication_stale: manifest_hash_changed && manifest.publication.is_some(),
+        project: manifest.project,
+        assistance_notes: manifest.assistance.notes,
+        bundle_root: hashes.bundle_root_sha256,
+        manifest_hash_changed,
+    })
+}
+
+pub fn format_project_report(report: &ProjectReport) -> String {
+    let project = &report.project;
+    let mut out = format!("Title: {}\n", project.title);
+    if let Some(author) = &project.author {
+        let _ = writeln!(out, "Author: {}", author);
+    }
+    if let Some(audience) = &project.audience {
+        let _ = writeln!(out, "Audience: {}", audience);
+    }
+    for link in project.links.iter().flatten() {
+        let _ = writeln!(out, "Link: {}", link);
+    }
+    if let Some(notes) = &report.assistance_notes {
+        let _ = writeln!(out, "Assistance notes: {}", notes);
+    }
+    let _ = writeln!(out, "Proof paths: {}", report.path_policy);
+    let _ = writeln!(out, "Bundle root: {}", report.bundle_root);
+    if report.receipt_invalidated {
+        let _ = writeln!(
+            out,
+            "Warning: existing receipt no longer matches. Run `disclose stamp` again."
+        );
+    }
+    if report.publication_stale {
+        let _ = writeln!(out, "Warning: the published disclosure is now out of date.");
+    }
+    out.trim_end().to_string()
+}
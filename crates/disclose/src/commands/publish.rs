@@ -0,0 +1,263 @@
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::errors::ValidationError;
+use crate::hashing::build_hashes;
+use crate::manifest::{DisclosureManifest, PathPolicy, PublicationInfo};
+use crate::pii::{ensure_allowed, format_findings, scan_manifest, Finding};
+use crate::publish::{PublishPayload, PublishTarget, ReceiptPayload, TargetKind};
+use crate::workspace::Workspace;
+
+use super::{apply_path_policy, ensure_migrated};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishReport {
+    pub slug: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>,
+    pub target: TargetKind,
+    /// The existing publication was updated in place rather than created.
+    pub updated: bool,
+    /// Slug of the publication left behind by `--new`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptSize {
+    pub filename: String,
+    pub bytes: u64,
+}
+
+/// Everything `publish` is about to send, built before anything leaves the
+/// machine so it can be previewed with `--dry-run` or confirmed.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishPlan {
+    pub target: TargetKind,
+    pub method: String,
+    pub url: String,
+    /// Slug of the publication this request replaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updates: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+    /// The exact request body.
+    #[serde(skip)]
+    pub body: String,
+    pub body_bytes: usize,
+    pub receipts: Vec<ReceiptSize>,
+    /// Emails, phone numbers, keys and home paths found in the manifest.
+    /// Sending is blocked while there are any unless explicitly allowed.
+    pub findings: Vec<Finding>,
+}
+
+fn read_receipts(workspace: &Workspace) -> Result<(Vec<ReceiptPayload>, Vec<ReceiptSize>)> {
+    let mut receipts = Vec::new();
+    let mut sizes = Vec::new();
+    if workspace.receipts_dir().exists() {
+        for entry in fs::read_dir(workspace.receipts_dir())? {
+            let entry = entry?;
+            let bytes = fs::read(entry.path())?;
+            let filename = entry.file_name().to_string_lossy().to_string();
+            sizes.push(ReceiptSize {
+                filename: filename.clone(),
+                bytes: bytes.len() as u64,
+            });
+            receipts.push(ReceiptPayload {
+                filename,
+                bytes_base64: general_purpose::STANDARD.encode(bytes),
+            });
+        }
+    }
+    Ok((receipts, sizes))
+}
+
+fn published_slug(manifest: &DisclosureManifest) -> Option<String> {
+    manifest
+        .publication
+        .as_ref()
+        .and_then(|publication| publication.slug.clone())
+}
+
+/// The target holding the current publication; publications without one
+/// were made through the web API.
+fn published_target(manifest: &DisclosureManifest) -> Result<TargetKind> {
+    match manifest
+        .publication
+        .as_ref()
+        .and_then(|publication| publication.target.as_deref())
+    {
+        Some(target) => Ok(target.parse()?),
+        None => Ok(TargetKind::Api),
+    }
+}
+
+/// Builds the publish request without sending it. A workspace that was
+/// published to the same target before updates its existing slug (the old
+/// version is kept as a revision) unless `fork` asks for a separate
+/// publication. Proof paths are redacted under `path_policy`, or the
+/// manifest's policy.
+pub fn plan_publish(
+    workspace: &Workspace,
+    target: &dyn PublishTarget,
+    include_receipts: bool,
+    fork: bool,
+    path_policy: Option<PathPolicy>,
+) -> Result<PublishPlan> {
+    workspace.ensure_writable()?;
+    ensure_migrated(workspace)?;
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    apply_path_policy(&mut manifest, path_policy)?;
+    let hashes = build_hashes(&manifest)?;
+    let (receipts, receipt_sizes) = if include_receipts {
+        read_receipts(workspace)?
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let findings = scan_manifest(&manifest)?;
+    let body = PublishPayload {
+        manifest: &manifest,
+        hashes: &hashes,
+        receipts,
+    }
+    .to_body()?;
+
+    let existing = published_slug(&manifest);
+    let same_target = published_target(&manifest)? == target.kind();
+    let (updates, forked_from) = match (existing, fork || !same_target) {
+        (Some(slug), false) => (Some(slug), None),
+        (existing, _) => (None, existing),
+    };
+    let (method, url) = target.describe(updates.as_deref());
+    Ok(PublishPlan {
+        target: target.kind(),
+        method,
+        url,
+        updates,
+        forked_from,
+        body_bytes: body.len(),
+        body,
+        receipts: receipt_sizes,
+        findings,
+    })
+}
+
+/// Sends a planned publish request and records the publication in
+/// `disclosure.json`. Refuses when the plan has findings, unless
+/// `allow_sensitive` is set.
+pub async fn send_publish_plan(
+    workspace: &Workspace,
+    target: &dyn PublishTarget,
+    token: Option<String>,
+    plan: PublishPlan,
+    allow_sensitive: bool,
+) -> Result<PublishReport> {
+    ensure_allowed(&plan.findings, allow_sensitive)?;
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let response = target
+        .publish(plan.updates.as_deref(), plan.body, token)
+        .await?;
+    let updated = plan.updates.is_some();
+    let (published_at, updated_at) = if updated {
+        let published_at = manifest
+            .publication
+            .as_ref()
+            .and_then(|publication| publication.published_at.clone());
+        (published_at, Some(Utc::now().to_rfc3339()))
+    } else {
+        (Some(Utc::now().to_rfc3339()), None)
+    };
+    manifest.publication = Some(PublicationInfo {
+        slug: Some(response.slug.clone()),
+        url: Some(response.url.clone()),
+        published_at,
+        updated_at,
+        revision: response.revision,
+        target: match target.kind() {
+            TargetKind::Api => None,
+            kind => Some(kind.to_string()),
+        },
+        extra: Default::default(),
+    });
+    manifest.write_to(&workspace.disclosure_path())?;
+    Ok(PublishReport {
+        slug: response.slug,
+        url: response.url,
+        revision: response.revision,
+        target: target.kind(),
+        updated,
+        forked_from: if updated { None } else { plan.forked_from },
+    })
+}
+
+pub async fn publish_workspace(
+    workspace: &Workspace,
+    target: &dyn PublishTarget,
+    token: Option<String>,
+    include_receipts: bool,
+    fork: bool,
+    path_policy: Option<PathPolicy>,
+    allow_sensitive: bool,
+) -> Result<PublishReport> {
+    let plan = plan_publish(workspace, target, include_receipts, fork, path_policy)?;
+    send_publish_plan(workspace, target, token, plan, allow_sensitive).await
+}
+
+/// Human summary of a plan: target, size, receipts and scanner findings.
+pub fn format_publish_plan(plan: &PublishPlan) -> String {
+    let mut out = format!(
+        "{} {} ({} bytes, {} target)",
+        plan.method, plan.url, plan.body_bytes, plan.target
+    );
+    if let Some(slug) = &plan.updates {
+        let _ = write!(out, "\nUpdates publication {}", slug);
+    }
+    if let Some(slug) = &plan.forked_from {
+        let _ = write!(out, "\nPublishes a new copy; {} stays online", slug);
+    }
+    if plan.receipts.is_empty() {
+        out.push_str("\nReceipts: none");
+    } else {
+        out.push_str("\nReceipts:");
+        for receipt in &plan.receipts {
+            let _ = write!(out, "\n  {} ({} bytes)", receipt.filename, receipt.bytes);
+        }
+    }
+    if plan.findings.is_empty() {
+        out.push_str("\nNo personal data or secrets found");
+    } else {
+        out.push('\n');
+        out.push_str(&format_findings(&plan.findings));
+    }
+    out
+}
+
+/// Deletes the workspace's publication and forgets it locally. Returns the
+/// removed slug.
+pub async fn unpublish_workspace(
+    workspace: &Workspace,
+    target: &dyn PublishTarget,
+    token: Option<String>,
+) -> Result<String> {
+    workspace.ensure_writable()?;
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let slug = published_slug(&manifest)
+        .ok_or_else(|| ValidationError::new("This disclosure has not been published"))?;
+    let published = published_target(&manifest)?;
+    if published != target.kind() {
+        return Err(ValidationError::new(format!(
+            "Publication {} is on the {} target; pass --target {} to remove it",
+            slug, published, published
+        ))
+        .into());
+    }
+    target.unpublish(&slug, token).await?;
+    manifest.publication = None;
+    manifest.write_to(&workspace.disclosure_path())?;
+    Ok(slug)
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use globset::Glob;
+
+use super::load_template_from_manifest;
+use crate::errors::ValidationError;
+use crate::manifest::{
+    AssistanceGrade, AssistanceScope, DisclosureManifest, ProofItem, ProofKind, ScopedAssistance,
+};
+use crate::workspace::Workspace;
+
+/// Parses `proof:<id|label>`, `glob:<pattern>` or `component:<name>`.
+pub fn parse_scope(input: &str) -> Result<AssistanceScope> {
+    let (kind, value) = input.split_once(':').ok_or_else(|| {
+        ValidationError::new(format!(
+            "Invalid scope {}, expected proof:<id>, glob:<pattern> or component:<name>",
+            input
+        ))
+    })?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(ValidationError::new(format!("Scope {} has an empty value", input)).into());
+    }
+    match kind {
+        "proof" => Ok(AssistanceScope::Proof(value.to_string())),
+        "glob" => {
+            Glob::new(value)
+                .map_err(|err| ValidationError::new(format!("Invalid glob {}: {}", value, err)))?;
+            Ok(AssistanceScope::Glob(value.to_string()))
+        }
+        "component" => Ok(AssistanceScope::Component(value.to_string())),
+        _ => Err(ValidationError::new(format!(
+            "Unknown scope kind {}, expected proof, glob or component",
+            kind
+        ))
+        .into()),
+    }
+}
+
+/// Proof items covered by a scope. Components are free-form and cover none.
+pub fn scope_proof_ids(scope: &AssistanceScope, items: &[ProofItem]) -> Vec<String> {
+    match scope {
+        AssistanceScope::Proof(id) => items
+            .iter()
+            .filter(|item| &item.id == id)
+            .map(|item| item.id.clone())
+            .collect(),
+        AssistanceScope::Glob(pattern) => {
+            let Ok(glob) = Glob::new(pattern) else {
+                return Vec::new();
+            };
+            let matcher = glob.compile_matcher();
+            items
+                .iter()
+                .filter(|item| matches!(item.kind, ProofKind::File))
+                .filter(|item| {
+                    item.path
+                        .as_deref()
+                        .is_some_and(|path| matcher.is_match(path))
+                })
+                .map(|item| item.id.clone())
+                .collect()
+        }
+        AssistanceScope::Component(_) => Vec::new(),
+    }
+}
+
+/// Proof scopes may name an item by label; they are always stored by id.
+fn resolve_proof_scope(scope: AssistanceScope, items: &[ProofItem]) -> Result<AssistanceScope> {
+    let AssistanceScope::Proof(reference) = &scope else {
+        return Ok(scope);
+    };
+    if items.iter().any(|item| &item.id == reference) {
+        return Ok(scope);
+    }
+    let by_label: Vec<&ProofItem> = items
+        .iter()
+        .filter(|item| &item.label == reference)
+        .collect();
+    match by_label.as_slice() {
+        [item] => Ok(AssistanceScope::Proof(item.id.clone())),
+        [] => Err(
+            ValidationError::new(format!("No proof item with id or label {}", reference)).into(),
+        ),
+        _ => Err(ValidationError::new(format!(
+            "Several proof items are labelled {}; use the proof id",
+            reference
+        ))
+        .into()),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScopeUpdate {
+    pub scope: AssistanceScope,
+    pub ai_percent: Option<i32>,
+    pub human_percent: Option<i32>,
+    pub grade: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// What `disclose meter` does to scoped assistance.
+#[derive(Debug, Clone)]
+pub enum ScopeChange {
+    Set(ScopeUpdate),
+    Remove(AssistanceScope),
+}
+
+/// Adds or replaces the assistance entry for one scope. A grade on its own
+/// records the grade's point value; with an explicit percentage the
+/// percentage must fall inside the grade's range.
+pub(super) fn set_scoped_assistance(
+    workspace: &Workspace,
+    manifest: &mut DisclosureManifest,
+    update: ScopeUpdate,
+) -> Result<()> {
+    let scope = resolve_proof_scope(update.scope, &manifest.proof.items)?;
+    let mut entries = manifest.assistance.scoped.take().unwrap_or_default();
+    let existing = entries.iter().position(|entry| entry.scope == scope);
+
+    let level = match &update.grade {
+        Some(grade) => Some(load_template_from_manifest(workspace, manifest)?.grade(grade)?),
+        None => None,
+    };
+    let ai = match (update.ai_percent, update.human_percent) {
+        (Some(ai), Some(human)) if ai + human != 100 => {
+            return Err(ValidationError::new("Scoped split must sum to 100").into());
+        }
+        (Some(ai), _) => Some(ai),
+        (None, Some(human)) => Some(100 - human),
+        (None, None) => level.as_ref().map(|level| level.point_percent()),
+    };
+    let ai = match (ai, existing) {
+        (Some(ai), _) => ai,
+        (None, Some(index)) => entries[index].ai_percent,
+        (None, None) => {
+            return Err(ValidationError::new(format!(
+                "Provide --scope-ai, --scope-human or --scope-grade for {}",
+                scope
+            ))
+            .into());
+        }
+    };
+    if !(0..=100).contains(&ai) {
+        return Err(ValidationError::new("Scoped split must be between 0 and 100").into());
+    }
+    if let Some(level) = &level {
+        if !level.contains(ai) {
+            return Err(ValidationError::new(format!(
+                "{}% AI is outside grade {} ({}-{}%)",
+                ai, level.key, level.min_percent, level.max_percent
+            ))
+            .into());
+        }
+    }
+
+    let mut entry = ScopedAssistance {
+        scope: scope.clone(),
+        human_percent: 100 - ai,
+        ai_percent: ai,
+        grade: level
+            .as_ref()
+            .map(|level| AssistanceGrade::new(level.key.clone())),
+        ai_percent_range: level.as_ref().map(|level| level.range()),
+        notes: update.notes,
+        extra: Default::default(),
+    };
+    match existing {
+        Some(index) => {
+            let previous = &mut entries[index];
+            if entry.grade.is_none()
+                && previous.grade.is_some()
+                && update.ai_percent.is_none()
+                && update.human_percent.is_none()
+            {
+                entry.grade = previous.grade.take();
+                entry.ai_percent_range = previous.ai_percent_range.take();
+            }
+            if entry.notes.is_none() {
+                entry.notes = previous.notes.take();
+            }
+            entry.extra = std::mem::take(&mut previous.extra);
+            *previous = entry;
+        }
+        None => entries.push(entry),
+    }
+    manifest.assistance.scoped = Some(entries);
+    Ok(())
+}
+
+pub(super) fn remove_scoped_assistance(
+    manifest: &mut DisclosureManifest,
+    scope: AssistanceScope,
+) -> Result<()> {
+    let scope = resolve_proof_scope(scope.clone(), &manifest.proof.items).unwrap_or(scope);
+    let mut entries = manifest.assistance.scoped.take().unwrap_or_default();
+    let before = entries.len();
+    entries.retain(|entry| entry.scope != scope);
+    if entries.len() == before {
+        return Err(ValidationError::new(format!("No assistance entry for {}", scope)).into());
+    }
+    manifest.assistance.scoped = (!entries.is_empty()).then_some(entries);
+    Ok(())
+}
@@ -0,0 +1,403 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+use super::load_template_from_manifest;
+use super::scopes::scope_proof_ids;
+use super::tools::format_tool;
+use crate::completeness::compute_completeness;
+use crate::hashing::{build_stored_hashes, hash_file, HashesJson};
+use crate::manifest::{
+    AiTool, AssistanceGlobal, AssistanceScope, AssistanceStage, DisclosureManifest,
+    ProofCompleteness, ProofKind, PublicationInfo, ScopedAssistance,
+};
+use crate::migrations::detect_version;
+use crate::ots;
+use crate::workspace::Workspace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofFileState {
+    Unchanged,
+    Modified,
+    Missing,
+    NotAFile,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProofStatus {
+    pub id: String,
+    pub label: String,
+    pub kind: ProofKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub sha256: String,
+    pub state: ProofFileState,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScopedStatus {
+    #[serde(flatten)]
+    pub entry: ScopedAssistance,
+    /// Proof items the scope currently covers.
+    pub proof_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HashesStatus {
+    pub present: bool,
+    pub up_to_date: bool,
+    pub manifest_sha256: String,
+    pub bundle_root_sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReceiptStatus {
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    pub present: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256_matches: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceStatus {
+    pub id: String,
+    /// Manifest version as stored on disk.
+    pub version: String,
+    /// Set when `disclose migrate` would move the manifest to this version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migrates_to: Option<String>,
+    pub template: String,
+    pub template_version: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub global: AssistanceGlobal,
+    /// AI share implied by the weighted stage grades.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derived_ai_percent: Option<i32>,
+    pub stages: Vec<AssistanceStage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scoped: Vec<ScopedStatus>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ai_tools: Vec<AiTool>,
+    pub proof: Vec<ProofStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<ProofCompleteness>,
+    pub hashes: HashesStatus,
+    pub receipt: ReceiptStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publication: Option<PublicationInfo>,
+    /// Source URL when this is a read-only copy saved by `disclose fetch`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched_from: Option<String>,
+}
+
+fn proof_state(
+    workspace: &Workspace,
+    kind: &ProofKind,
+    path: Option<&str>,
+    sha256: &str,
+) -> ProofFileState {
+    if !matches!(kind, ProofKind::File) {
+        return ProofFileState::NotAFile;
+    }
+    let Some(path) = path else {
+        return ProofFileState::Missing;
+    };
+    let full = workspace.root_path().join(path);
+    if !full.is_file() {
+        return ProofFileState::Missing;
+    }
+    match hash_file(&full) {
+        Ok(actual) if actual == sha256 => ProofFileState::Unchanged,
+        Ok(_) => ProofFileState::Modified,
+        Err(_) => ProofFileState::Missing,
+    }
+}
+
+fn receipt_status(workspace: &Workspace, manifest: &DisclosureManifest) -> Result<ReceiptStatus> {
+    let info = manifest
+        .timestamps
+        .as_ref()
+        .and_then(|timestamps| timestamps.opentimestamps.as_ref());
+    let Some(info) = info else {
+        return Ok(ReceiptStatus {
+            status: "none".to_string(),
+            filename: None,
+            present: false,
+            sha256_matches: None,
+        });
+    };
+    let filename = info.receipt_filename.clone();
+    let receipt_path = workspace
+        .receipts_dir()
+        .join(filename.as_deref().unwrap_or("bundle-root.ots"));
+    let present = receipt_path.is_file();
+    let sha256_matches = match (&info.receipt_sha256, present) {
+        (Some(expected), true) => Some(&ots::receipt_sha256(&receipt_path)? == expected),
+        _ => None,
+    };
+    Ok(ReceiptStatus {
+        status: info.status.clone().unwrap_or_else(|| "none".to_string()),
+        filename,
+        present,
+        sha256_matches,
+    })
+}
+
+pub fn workspace_status(workspace: &Workspace) -> Result<WorkspaceStatus> {
+    // Hash the stored bytes: a manifest from an older CLI is migrated in
+    // memory, and that copy would never match its own hashes.json.
+    let stored_manifest = DisclosureManifest::read_value(&workspace.disclosure_path())?;
+    let version = detect_version(&stored_manifest)?;
+    let manifest = DisclosureManifest::from_value(stored_manifest.clone())?;
+    let current = build_stored_hashes(&stored_manifest, &manifest)?;
+    let stored = if workspace.hashes_path().exists() {
+        let data = std::fs::read_to_string(workspace.hashes_path())?;
+        Some(serde_json::from_str::<HashesJson>(&data)?)
+    } else {
+        None
+    };
+
+    let template = load_template_from_manifest(workspace, &manifest).ok();
+    let proof = manifest
+        .proof
+        .items
+        .iter()
+        .map(|item| ProofStatus {
+            id: item.id.clone(),
+            label: item.label.clone(),
+            kind: item.kind.clone(),
+            path: item.path.clone(),
+            sha256: item.sha256.clone(),
+            state: proof_state(workspace, &item.kind, item.path.as_deref(), &item.sha256),
+        })
+        .collect();
+
+    let hashes = HashesStatus {
+        present: stored.is_some(),
+        up_to_date: stored
+            .as_ref()
+            .map(|hashes| hashes.bundle_root_sha256 == current.bundle_root_sha256)
+            .unwrap_or(false),
+        manifest_sha256: current.manifest_sha256,
+        bundle_root_sha256: current.bundle_root_sha256,
+    };
+
+    Ok(WorkspaceStatus {
+        id: manifest.id.clone(),
+        migrates_to: (version != manifest.version).then(|| manifest.version.clone()),
+        version,
+        template: manifest.template.slug.clone(),
+        template_version: manifest.template.version.clone(),
+        title: manifest.project.title.clone(),
+        author: manifest.project.author.clone(),
+        global: manifest.assistance.global.clone(),
+        derived_ai_percent: template.as_ref().and_then(|template| {
+            template.weighted_ai_percent(manifest.assistance.stages.as_deref().unwrap_or_default())
+        }),
+        stages: manifest.assistance.stages.clone().unwrap_or_default(),
+        ai_tools: manifest.ai_tools.clone().unwrap_or_default(),
+        scoped: manifest
+            .assistance
+            .scoped
+            .iter()
+            .flatten()
+            .map(|entry| ScopedStatus {
+                entry: entry.clone(),
+                proof_ids: scope_proof_ids(&entry.scope, &manifest.proof.items),
+            })
+            .collect(),
+        proof,
+        completeness: template
+            .as_ref()
+            .map(|template| compute_completeness(template, &manifest.proof.items))
+            .or_else(|| manifest.proof.completeness.clone()),
+        hashes,
+        receipt: receipt_status(workspace, &manifest)?,
+        publication: manifest.publication.clone(),
+        fetched_from: workspace.fetched_from(),
+    })
+}
+
+fn state_label(state: ProofFileState) -> &'static str {
+    match state {
+        ProofFileState::Unchanged => "ok",
+        ProofFileState::Modified => "MODIFIED",
+        ProofFileState::Missing => "MISSING",
+        ProofFileState::NotAFile => "-",
+    }
+}
+
+pub fn format_status(status: &WorkspaceStatus) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", status.title);
+    if let Some(url) = &status.fetched_from {
+        let _ = writeln!(out, "  Read-only copy fetched from {}", url);
+    }
+    if let Some(author) = &status.author {
+        let _ = writeln!(out, "  Author:    {}", author);
+    }
+    let _ = writeln!(
+        out,
+        "  Template:  {} (v{})",
+        status.template, status.template_version
+    );
+    if let Some(to) = &status.migrates_to {
+        let _ = writeln!(
+            out,
+            "  Manifest:  v{} (run `disclose migrate` to update to v{})",
+            status.version, to
+        );
+    }
+    let _ = writeln!(
+        out,
+        "  Split:     {}% human / {}% AI{}",
+        status.global.human_percent,
+        status.global.ai_percent,
+        status
+            .derived_ai_percent
+            .filter(|derived| *derived != status.global.ai_percent)
+            .map(|derived| format!(" (stages suggest ~{}% AI)", derived))
+            .unwrap_or_default()
+    );
+
+    let _ = writeln!(out, "\nStages");
+    if status.stages.is_empty() {
+        let _ = writeln!(out, "  (none)");
+    }
+    for stage in &status.stages {
+        let range = stage
+            .ai_percent_range
+            .as_ref()
+            .map(|range| format!(" ({}-{}%)", range.min, range.max))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  {:<20} {:<10} ~{}% AI{}",
+            stage.label,
+            stage.grade.as_str(),
+            stage.approx_ai_percent,
+            range
+        );
+    }
+
+    if !status.scoped.is_empty() {
+        let _ = writeln!(out, "\nScoped assistance");
+    }
+    for scoped in &status.scoped {
+        let entry = &scoped.entry;
+        let covers = match entry.scope {
+            AssistanceScope::Component(_) => String::new(),
+            _ => format!(", {} proof item(s)", scoped.proof_ids.len()),
+        };
+        let _ = writeln!(
+            out,
+            "  {:<28} {}% human / {}% AI{}{}",
+            entry.scope.to_string(),
+            entry.human_percent,
+            entry.ai_percent,
+            entry
+                .grade
+                .as_ref()
+                .map(|grade| format!(" ({})", grade.as_str()))
+                .unwrap_or_default(),
+            covers
+        );
+        if let Some(notes) = &entry.notes {
+            let _ = writeln!(out, "    {}", notes);
+        }
+    }
+
+    if !status.ai_tools.is_empty() {
+        let _ = writeln!(out, "\nAI tools");
+    }
+    for tool in &status.ai_tools {
+        let _ = writeln!(out, "  {}", format_tool(tool));
+    }
+
+    let _ = writeln!(out, "\nProof");
+    if status.proof.is_empty() {
+        let _ = writeln!(out, "  (none)");
+    }
+    for item in &status.proof {
+        let _ = writeln!(
+            out,
+            "  [{:<8}] {} ({})",
+            state_label(item.state),
+            item.label,
+            item.path.as_deref().unwrap_or("-")
+        );
+    }
+
+    if let Some(completeness) = &status.completeness {
+        if !completeness.slots.is_empty() {
+            let _ = writeln!(
+                out,
+                "\nRecommended proof ({}% complete)",
+                completeness.score_percent
+            );
+            out.push_str(&format_checklist(completeness));
+        }
+    }
+
+    let _ = writeln!(out, "\nHashes");
+    let _ = writeln!(out, "  Bundle root: {}", status.hashes.bundle_root_sha256);
+    let hashes_state = match (status.hashes.present, status.hashes.up_to_date) {
+        (false, _) => "hashes.json missing",
+        (true, true) => "hashes.json up to date",
+        (true, false) => "hashes.json stale",
+    };
+    let _ = writeln!(out, "  {}", hashes_state);
+
+    let _ = writeln!(out, "\nReceipt");
+    let receipt_state = match (status.receipt.present, status.receipt.sha256_matches) {
+        (true, Some(false)) => " (file does not match recorded sha256)",
+        (false, _) if status.receipt.status != "none" => " (file missing)",
+        _ => "",
+    };
+    let _ = writeln!(
+        out,
+        "  {}{}{}",
+        status.receipt.status,
+        status
+            .receipt
+            .filename
+            .as_deref()
+            .map(|name| format!(" - {}", name))
+            .unwrap_or_default(),
+        receipt_state
+    );
+
+    let _ = writeln!(out, "\nPublication");
+    match status
+        .publication
+        .as_ref()
+        .and_then(|publication| Some((publication.url.as_deref()?, publication.revision)))
+    {
+        Some((url, Some(revision))) => {
+            let _ = writeln!(out, "  {} (revision {})", url, revision);
+        }
+        Some((url, None)) => {
+            let _ = writeln!(out, "  {}", url);
+        }
+        None => {
+            let _ = writeln!(out, "  not published");
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+/// One line per recommended proof, e.g. `  [x] Raw notes`.
+pub fn format_checklist(completeness: &ProofCompleteness) -> String {
+    let mut out = String::new();
+    for slot in &completeness.slots {
+        let mark = if slot.proof_ids.is_empty() { " " } else { "x" };
+        let _ = writeln!(out, "  [{}] {}", mark, slot.label);
+    }
+    out
+}
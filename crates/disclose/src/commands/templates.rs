@@ -0,0 +1,457 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::{
+    apply_meter, save_manifest, MeterChanges, SplitCheck, SplitMode, DEFAULT_SPLIT_TOLERANCE,
+};
+use crate::completeness::compute_completeness;
+use crate::errors::ValidationError;
+use crate::manifest::{AssistanceGrade, AssistanceStage, DisclosureManifest};
+use crate::templates::{load_template_file, Template, TemplateCatalog, TemplateSource};
+use crate::version::compare_versions;
+use crate::workspace::Workspace;
+
+#[derive(Debug, Serialize)]
+pub struct SkippedTemplate {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateListing {
+    pub templates: Vec<Template>,
+    pub skipped: Vec<SkippedTemplate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateLint {
+    pub path: PathBuf,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+pub fn list_templates(workspace_root: Option<&Path>) -> Result<TemplateListing> {
+    let catalog = TemplateCatalog::discover(workspace_root)?;
+    Ok(TemplateListing {
+        templates: catalog.latest(),
+        skipped: catalog
+            .skipped()
+            .iter()
+            .map(|(path, error)| SkippedTemplate {
+                path: path.clone(),
+                error: error.clone(),
+            })
+            .collect(),
+    })
+}
+
+pub fn show_template(workspace_root: Option<&Path>, slug: &str) -> Result<Template> {
+    TemplateCatalog::discover(workspace_root)?.get(slug)
+}
+
+/// Checks a candidate template file the same way discovery does, plus a few
+/// warnings for things that load fine but are probably mistakes.
+pub fn lint_template(workspace_root: Option<&Path>, path: &Path) -> Result<TemplateLint> {
+    let mut lint = TemplateLint {
+        path: path.to_path_buf(),
+        valid: false,
+        slug: None,
+        version: None,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let template = match load_template_file(path, TemplateSource::File(path.to_path_buf())) {
+        Ok(template) => template,
+        Err(err) => {
+            lint.errors.push(err.to_string());
+            return Ok(lint);
+        }
+    };
+    lint.valid = true;
+    lint.slug = Some(template.slug.clone());
+    lint.version = Some(template.version.clone());
+
+    if template.recommended_proof.is_empty() {
+        lint.warnings
+            .push("recommended_proof is empty; readers get no proof checklist".to_string());
+    }
+    let mut labels = std::collections::HashSet::new();
+    for stage in &template.stages {
+        if !labels.insert(stage.label.to_lowercase()) {
+            lint.warnings.push(format!(
+                "stage label {} is used more than once",
+                stage.label
+            ));
+        }
+    }
+    let mut scale = template.grade_scale();
+    scale.sort_by_key(|level| level.min_percent);
+    for pair in scale.windows(2) {
+        if pair[1].min_percent <= pair[0].max_percent {
+            lint.warnings.push(format!(
+                "grades {} and {} overlap",
+                pair[0].key, pair[1].key
+            ));
+        } else if pair[1].min_percent > pair[0].max_percent + 1 {
+            lint.warnings.push(format!(
+                "no grade covers {}-{}%",
+                pair[0].max_percent + 1,
+                pair[1].min_percent - 1
+            ));
+        }
+    }
+    let catalog = TemplateCatalog::discover(workspace_root)?;
+    if let Ok(existing) = catalog.get(&template.slug) {
+        if compare_versions(&template.version, &existing.version).is_le() {
+            lint.warnings.push(format!(
+                "slug {} is already provided at version {} ({}); this file would be ignored",
+                template.slug,
+                existing.version,
+                source_label(&existing.source)
+            ));
+        }
+    }
+    Ok(lint)
+}
+
+pub fn source_label(source: &TemplateSource) -> String {
+    match source {
+        TemplateSource::Builtin => "built-in".to_string(),
+        TemplateSource::User(path) => format!("user: {}", path.display()),
+        TemplateSource::Workspace(path) => format!("workspace: {}", path.display()),
+        TemplateSource::File(path) => format!("file: {}", path.display()),
+    }
+}
+
+pub fn format_template_list(listing: &TemplateListing) -> String {
+    let mut out = String::new();
+    for template in &listing.templates {
+        let _ = writeln!(
+            out,
+            "{:<16} {:<8} {:<36} {} stages  [{}]",
+            template.slug,
+            template.version,
+            template.label,
+            template.stages.len(),
+            source_label(&template.source)
+        );
+    }
+    for skipped in &listing.skipped {
+        let _ = writeln!(out, "Skipped {}: {}", skipped.path.display(), skipped.error);
+    }
+    out.trim_end().to_string()
+}
+
+pub fn format_template(template: &Template) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{} ({})", template.label, template.slug);
+    let _ = writeln!(out, "  Version: {}", template.version);
+    let _ = writeln!(out, "  Source:  {}", source_label(&template.source));
+    let _ = writeln!(out, "\nStages");
+    for stage in &template.stages {
+        let weight = stage
+            .weight
+            .map(|weight| format!("  (weight {})", weight))
+            .unwrap_or_default();
+        let _ = writeln!(out, "  {:<20} {}{}", stage.key, stage.label, weight);
+    }
+    let _ = writeln!(out, "\nGrades");
+    for level in template.grade_scale() {
+        let _ = writeln!(
+            out,
+            "  {:<20} {:<12} {}-{}% (records {}%)",
+            level.key,
+            level.label,
+            level.min_percent,
+            level.max_percent,
+            level.point_percent()
+        );
+    }
+    let _ = writeln!(out, "\nRecommended proof");
+    if template.recommended_proof.is_empty() {
+        let _ = writeln!(out, "  (none)");
+    }
+    for proof in &template.recommended_proof {
+        let _ = writeln!(out, "  - {}", proof);
+    }
+    out.trim_end().to_string()
+}
+
+pub fn format_template_lint(lint: &TemplateLint) -> String {
+    let mut out = String::new();
+    if lint.valid {
+        let _ = writeln!(
+            out,
+            "{} is a valid template ({} v{})",
+            lint.path.display(),
+            lint.slug.as_deref().unwrap_or_default(),
+            lint.version.as_deref().unwrap_or_default()
+        );
+    } else {
+        let _ = writeln!(out, "{} is not a valid template", lint.path.display());
+    }
+    for error in &lint.errors {
+        let _ = writeln!(out, "  error: {}", error);
+    }
+    for warning in &lint.warnings {
+        let _ = writeln!(out, "  warning: {}", warning);
+    }
+    out.trim_end().to_string()
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TemplateUpgradeReport {
+    pub slug: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub kept: Vec<String>,
+    pub mapped: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Tool stage links that pointed at removed stages, as `tool: stage`.
+    pub dropped: Vec<String>,
+    /// Scoped entries whose grade is not on the new scale, as `scope: old -> new`.
+    pub regraded: Vec<String>,
+    /// The target is older than the version the workspace was on.
+    pub downgraded: bool,
+    pub receipt_invalidated: bool,
+    pub split: Option<SplitCheck>,
+}
+
+/// Moves a workspace to another version of its template. Stages are matched
+/// by key, or through explicit `old=new` mappings; unmatched target stages are
+/// added with no assistance and unmatched old stages are dropped, along with
+/// any tool links to them. Moving to an older version needs `allow_downgrade`.
+pub fn upgrade_template(
+    workspace: &Workspace,
+    to_version: Option<&str>,
+    mappings: Vec<(String, String)>,
+    allow_downgrade: bool,
+) -> Result<TemplateUpgradeReport> {
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let catalog = TemplateCatalog::discover(Some(workspace.root_path()))?;
+    let slug = manifest.template.slug.clone();
+    let target = match to_version {
+        Some(version) => catalog.get_version(&slug, version)?,
+        None => catalog.get(&slug)?,
+    };
+
+    let mut report = TemplateUpgradeReport {
+        slug: slug.clone(),
+        from_version: manifest.template.version.clone(),
+        to_version: target.version.clone(),
+        ..Default::default()
+    };
+    match compare_versions(&target.version, &manifest.template.version) {
+        Ordering::Equal => return Ok(report),
+        Ordering::Less if !allow_downgrade => {
+            return Err(ValidationError::new(format!(
+                "Template {} {} is older than the workspace's version {}; pass --allow-downgrade to move back",
+                slug, target.version, manifest.template.version
+            ))
+            .into())
+        }
+        Ordering::Less => report.downgraded = true,
+        Ordering::Greater => {}
+    }
+
+    for (old, new) in &mappings {
+        if !target.stages.iter().any(|stage| &stage.key == new) {
+            return Err(ValidationError::new(format!(
+                "Unknown stage key {} in template {} {}",
+                new, slug, target.version
+            ))
+            .into());
+        }
+        let known = manifest
+            .assistance
+            .stages
+            .iter()
+            .flatten()
+            .any(|stage| &stage.key == old);
+        if !known {
+            return Err(ValidationError::new(format!(
+                "Unknown stage key {} in this workspace",
+                old
+            ))
+            .into());
+        }
+    }
+
+    let old_stages = manifest.assistance.stages.clone().unwrap_or_default();
+    let mut consumed = std::collections::HashSet::new();
+    let mut stages = Vec::new();
+    for target_stage in &target.stages {
+        let mapped: Vec<&AssistanceStage> = old_stages
+            .iter()
+            .filter(|stage| {
+                mappings
+                    .iter()
+                    .any(|(old, new)| old == &stage.key && new == &target_stage.key)
+            })
+            .collect();
+        let same_key = old_stages.iter().find(|stage| {
+            stage.key == target_stage.key && !mappings.iter().any(|(old, _)| old == &stage.key)
+        });
+        // When several old stages feed one new stage, keep the heaviest grade.
+        let source = mapped
+            .iter()
+            .copied()
+            .chain(same_key)
+            .max_by_key(|stage| stage.approx_ai_percent);
+        match source {
+            Some(old) => {
+                for stage in mapped.iter().copied().chain(same_key) {
+                    consumed.insert(stage.key.clone());
+                }
+                if mapped.is_empty() {
+                    report.kept.push(target_stage.key.clone());
+                } else {
+                    for stage in &mapped {
+                        report
+                            .mapped
+                            .push(format!("{} -> {}", stage.key, target_stage.key));
+                    }
+                }
+                // Keep the grade when the new scale still has it, otherwise
+                // pick the grade whose range covers the old percentage.
+                let level = target
+                    .grade(old.grade.as_str())
+                    .unwrap_or_else(|_| target.grade_for_percent(old.approx_ai_percent));
+                let mut stage = level.stage(&target_stage.key, &target_stage.label);
+                stage.extra = old.extra.clone();
+                stages.push(stage);
+            }
+            None => {
+                report.added.push(target_stage.key.clone());
+                stages.push(
+                    target
+                        .lowest_grade()
+                        .stage(&target_stage.key, &target_stage.label),
+                );
+            }
+        }
+    }
+    report.removed = old_stages
+        .iter()
+        .filter(|stage| !consumed.contains(&stage.key))
+        .map(|stage| format!("{} ({})", stage.key, stage.grade.as_str()))
+        .collect();
+
+    // Tool links follow their stage to its new key or go away with it.
+    let renamed = |key: &str| -> Option<String> {
+        mappings
+            .iter()
+            .find(|(old, _)| old == key)
+            .map(|(_, new)| new.clone())
+            .or_else(|| {
+                target
+                    .stages
+                    .iter()
+                    .any(|stage| stage.key == key)
+                    .then(|| key.to_string())
+            })
+    };
+    for tool in manifest.ai_tools.iter_mut().flatten() {
+        let Some(keys) = tool.stages.take() else {
+            continue;
+        };
+        let mut linked: Vec<String> = Vec::new();
+        for key in keys {
+            match renamed(&key) {
+                Some(new) if !linked.contains(&new) => linked.push(new),
+                Some(_) => {}
+                None => report.dropped.push(format!("{}: {}", tool.name, key)),
+            }
+        }
+        tool.stages = Some(linked);
+    }
+    for entry in manifest.assistance.scoped.iter_mut().flatten() {
+        let Some(grade) = entry.grade.as_ref() else {
+            continue;
+        };
+        if target.grade(grade.as_str()).is_ok() {
+            continue;
+        }
+        let level = target.grade_for_percent(entry.ai_percent);
+        report.regraded.push(format!(
+            "{}: {} -> {}",
+            entry.scope,
+            grade.as_str(),
+            level.key
+        ));
+        entry.grade = Some(AssistanceGrade::new(level.key.clone()));
+        entry.ai_percent_range = Some(level.range());
+    }
+
+    manifest.assistance.stages = Some(stages);
+    manifest.template.version = target.version.clone();
+    manifest.proof.completeness = Some(compute_completeness(&target, &manifest.proof.items));
+    report.split = apply_meter(
+        workspace,
+        &mut manifest,
+        MeterChanges {
+            global_human: None,
+            global_ai: None,
+            stages: Vec::new(),
+            allow_unknown: false,
+            split_mode: SplitMode::Warn,
+            tolerance: DEFAULT_SPLIT_TOLERANCE,
+        },
+    )?;
+    save_manifest(workspace, &mut manifest)?;
+
+    report.receipt_invalidated = manifest.has_receipt();
+    Ok(report)
+}
+
+pub fn format_template_upgrade(report: &TemplateUpgradeReport) -> String {
+    let mut out = String::new();
+    if report.from_version == report.to_version {
+        let _ = writeln!(
+            out,
+            "Template {} is already at version {}",
+            report.slug, report.to_version
+        );
+        return out.trim_end().to_string();
+    }
+    let _ = writeln!(
+        out,
+        "{} template {} from {} to {}",
+        if report.downgraded {
+            "Downgraded"
+        } else {
+            "Upgraded"
+        },
+        report.slug,
+        report.from_version,
+        report.to_version
+    );
+    for (title, entries) in [
+        ("Kept", &report.kept),
+        ("Mapped", &report.mapped),
+        ("Added", &report.added),
+        ("Removed", &report.removed),
+        ("Dropped tool stages", &report.dropped),
+        ("Regraded scopes", &report.regraded),
+    ] {
+        if !entries.is_empty() {
+            let _ = writeln!(out, "  {}: {}", title, entries.join(", "));
+        }
+    }
+    if report.receipt_invalidated {
+        let _ = writeln!(
+            out,
+            "Warning: existing receipt no longer matches. Run `disclose stamp` again."
+        );
+    }
+    out.trim_end().to_string()
+}
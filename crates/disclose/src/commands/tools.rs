@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+
+use super::save_manifest;
+use crate::errors::ValidationError;
+use crate::hashing::HashesJson;
+use crate::manifest::{AiTool, DisclosureManifest};
+use crate::workspace::Workspace;
+
+fn same_name(tool: &AiTool, name: &str) -> bool {
+    tool.name.eq_ignore_ascii_case(name.trim())
+}
+
+/// Adds a tool to `ai_tools`. Names are unique (case-insensitive) and linked
+/// stages must be stages of this disclosure.
+pub fn add_tool(workspace: &Workspace, tool: AiTool) -> Result<HashesJson> {
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    if tool.name.trim().is_empty() {
+        return Err(ValidationError::new("Tool name must not be empty").into());
+    }
+    let mut tools = manifest.ai_tools.take().unwrap_or_default();
+    if tools.iter().any(|existing| same_name(existing, &tool.name)) {
+        return Err(ValidationError::new(format!(
+            "Tool {} is already listed; remove it first to change it",
+            tool.name
+        ))
+        .into());
+    }
+    let known: Vec<&str> = manifest
+        .assistance
+        .stages
+        .iter()
+        .flatten()
+        .map(|stage| stage.key.as_str())
+        .collect();
+    for key in tool.stages.iter().flatten() {
+        if !known.contains(&key.as_str()) {
+            return Err(ValidationError::new(format!(
+                "Unknown stage key {}. Available stages: {}",
+                key,
+                known.join(", ")
+            ))
+            .into());
+        }
+    }
+    tools.push(AiTool {
+        name: tool.name.trim().to_string(),
+        ..tool
+    });
+    manifest.ai_tools = Some(tools);
+    save_manifest(workspace, &mut manifest)
+}
+
+pub fn remove_tool(workspace: &Workspace, name: &str) -> Result<HashesJson> {
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let mut tools = manifest.ai_tools.take().unwrap_or_default();
+    let before = tools.len();
+    tools.retain(|tool| !same_name(tool, name));
+    if tools.len() == before {
+        return Err(ValidationError::new(format!("No AI tool named {}", name)).into());
+    }
+    manifest.ai_tools = (!tools.is_empty()).then_some(tools);
+    save_manifest(workspace, &mut manifest)
+}
+
+pub fn list_tools(workspace: &Workspace) -> Result<Vec<AiTool>> {
+    let manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    Ok(manifest.ai_tools.unwrap_or_default())
+}
+
+pub fn format_tool(tool: &AiTool) -> String {
+    let mut line = tool.name.clone();
+    let details: Vec<String> = [
+        tool.provider.clone(),
+        tool.model.clone(),
+        tool.version.as_ref().map(|version| format!("v{}", version)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !details.is_empty() {
+        let _ = write!(line, " ({})", details.join(", "));
+    }
+    if let Some(stages) = tool.stages.as_ref().filter(|stages| !stages.is_empty()) {
+        let _ = write!(line, " - stages: {}", stages.join(", "));
+    }
+    if let Some(notes) = &tool.notes {
+        let _ = write!(line, " - {}", notes);
+    }
+    line
+}
+
+pub fn format_tool_list(tools: &[AiTool]) -> String {
+    if tools.is_empty() {
+        return "No AI tools listed".to_string();
+    }
+    tools
+        .iter()
+        .map(|tool| format!("  {}", format_tool(tool)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,267 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use super::{
+    apply_meter, load_template_from_manifest, save_manifest, MeterChanges, SplitCheck, SplitMode,
+    DEFAULT_SPLIT_TOLERANCE,
+};
+use crate::errors::ValidationError;
+use crate::manifest::{AiTool, DisclosureManifest};
+use crate::templates::Template;
+use crate::usage::{parse_usage_file, summarize, Session, ToolUsage, UsageFormat, UsageSummary};
+use crate::workspace::Workspace;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposedTool {
+    #[serde(flatten)]
+    pub tool: AiTool,
+    pub already_listed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposedStage {
+    pub key: String,
+    pub grade: String,
+    pub matching_prompts: usize,
+    pub prompt_share_percent: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageImport {
+    pub summary: UsageSummary,
+    pub proposed_tools: Vec<ProposedTool>,
+    pub proposed_stages: Vec<ProposedStage>,
+    pub applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split: Option<SplitCheck>,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.len() >= 4)
+        .map(str::to_string)
+        .collect()
+}
+
+fn related(a: &str, b: &str) -> bool {
+    a == b
+        || a.chars()
+            .zip(b.chars())
+            .take_while(|(left, right)| left == right)
+            .count()
+            >= 4
+}
+
+/// Proposes a grade for each template stage that the prompts talk about,
+/// from the share of prompts mentioning the stage key or label. Prompts are
+/// only read here and never stored.
+fn propose_stages<'a>(
+    template: &Template,
+    sessions: impl IntoIterator<Item = &'a Session>,
+) -> Vec<ProposedStage> {
+    let prompts: Vec<Vec<String>> = sessions
+        .into_iter()
+        .flat_map(|session| session.prompts.iter().map(|prompt| words(prompt)))
+        .collect();
+    if prompts.is_empty() {
+        return Vec::new();
+    }
+    template
+        .stages
+        .iter()
+        .filter_map(|stage| {
+            let mut stage_words = words(&stage.key);
+            stage_words.extend(words(&stage.label));
+            let matching = prompts
+                .iter()
+                .filter(|prompt| {
+                    prompt
+                        .iter()
+                        .any(|word| stage_words.iter().any(|stage| related(word, stage)))
+                })
+                .count();
+            if matching == 0 {
+                return None;
+            }
+            let share = ((matching * 100 + prompts.len() / 2) / prompts.len()) as i32;
+            Some(ProposedStage {
+                key: stage.key.clone(),
+                grade: template.grade_for_percent(share).key,
+                matching_prompts: matching,
+                prompt_share_percent: share,
+            })
+        })
+        .collect()
+}
+
+fn usage_notes(usage: &ToolUsage) -> String {
+    let mut notes = format!(
+        "Imported usage: {} session(s), {} prompt(s)",
+        usage.sessions, usage.prompts
+    );
+    if let (Some(first), Some(last)) = (usage.first_seen, usage.last_seen) {
+        let _ = write!(
+            notes,
+            ", {} to {}",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d")
+        );
+    }
+    if usage.models.len() > 1 {
+        let _ = write!(notes, "; models: {}", usage.models.join(", "));
+    }
+    notes
+}
+
+fn propose_tool(
+    usage: &ToolUsage,
+    stages: &[ProposedStage],
+    manifest: &DisclosureManifest,
+) -> ProposedTool {
+    let already_listed = manifest
+        .ai_tools
+        .iter()
+        .flatten()
+        .any(|tool| tool.name.eq_ignore_ascii_case(&usage.name));
+    ProposedTool {
+        tool: AiTool {
+            name: usage.name.clone(),
+            provider: usage.provider.clone(),
+            model: usage.models.first().cloned(),
+            version: usage.version.clone(),
+            notes: Some(usage_notes(usage)),
+            stages: (!stages.is_empty())
+                .then(|| stages.iter().map(|stage| stage.key.clone()).collect()),
+            extra: Default::default(),
+        },
+        already_listed,
+    }
+}
+
+/// Reads assistant session logs and proposes `ai_tools` entries and stage
+/// grades. Nothing is written unless `apply` is set, and only counts, model
+/// names and dates ever reach the manifest.
+pub fn import_usage(
+    workspace: &Workspace,
+    files: &[PathBuf],
+    format: Option<UsageFormat>,
+    apply: bool,
+) -> Result<UsageImport> {
+    let mut manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    let template = load_template_from_manifest(workspace, &manifest)?;
+    let mut sessions = Vec::new();
+    for file in files {
+        sessions.extend(parse_usage_file(file, format)?);
+    }
+    if sessions.is_empty() {
+        return Err(ValidationError::new("No assistant sessions found in the given logs").into());
+    }
+    let summary = summarize(files.to_vec(), &sessions);
+    let proposed_stages = propose_stages(&template, &sessions);
+    // A tool is only linked to the stages its own sessions talk about.
+    let proposed_tools: Vec<ProposedTool> = summary
+        .tools
+        .iter()
+        .map(|usage| {
+            let own = sessions.iter().filter(|session| session.tool == usage.name);
+            propose_tool(usage, &propose_stages(&template, own), &manifest)
+        })
+        .collect();
+
+    let mut split = None;
+    if apply {
+        let new_tools: Vec<AiTool> = proposed_tools
+            .iter()
+            .filter(|proposal| !proposal.already_listed)
+            .map(|proposal| proposal.tool.clone())
+            .collect();
+        let changed = !new_tools.is_empty() || !proposed_stages.is_empty();
+        if !new_tools.is_empty() {
+            manifest
+                .ai_tools
+                .get_or_insert_with(Vec::new)
+                .extend(new_tools);
+        }
+        if !proposed_stages.is_empty() {
+            let stages = proposed_stages
+                .iter()
+                .map(|stage| (stage.key.clone(), stage.grade.clone()))
+                .collect();
+            split = apply_meter(
+                workspace,
+                &mut manifest,
+                MeterChanges {
+                    global_human: None,
+                    global_ai: None,
+                    stages,
+                    allow_unknown: false,
+                    split_mode: SplitMode::Warn,
+                    tolerance: DEFAULT_SPLIT_TOLERANCE,
+                },
+            )?;
+        }
+        // Tools and grades are written together or not at all.
+        if changed {
+            save_manifest(workspace, &mut manifest)?;
+        }
+    }
+
+    Ok(UsageImport {
+        summary,
+        proposed_tools,
+        proposed_stages,
+        applied: apply,
+        split,
+    })
+}
+
+pub fn format_usage_import(import: &UsageImport) -> String {
+    let summary = &import.summary;
+    let mut out = format!(
+        "Read {} session(s) with {} prompt(s) from {} file(s)",
+        summary.sessions,
+        summary.prompts,
+        summary.files.len()
+    );
+    if let (Some(first), Some(last)) = (summary.first_seen, summary.last_seen) {
+        let _ = write!(
+            out,
+            ", {} to {}",
+            first.format("%Y-%m-%d %H:%M"),
+            last.format("%Y-%m-%d %H:%M")
+        );
+    }
+    out.push_str("\n\nProposed AI tools:");
+    for proposal in &import.proposed_tools {
+        let marker = if proposal.already_listed {
+            " (already listed, skipped)"
+        } else {
+            ""
+        };
+        let _ = write!(
+            out,
+            "\n  {}{}",
+            super::tools::format_tool(&proposal.tool),
+            marker
+        );
+    }
+    if import.proposed_stages.is_empty() {
+        out.push_str("\n\nNo stage grades proposed");
+    } else {
+        out.push_str("\n\nProposed stage grades:");
+        for stage in &import.proposed_stages {
+            let _ = write!(
+                out,
+                "\n  {}: {} ({} prompt(s), {}%)",
+                stage.key, stage.grade, stage.matching_prompts, stage.prompt_share_percent
+            );
+        }
+    }
+    if import.applied {
+        out.push_str("\n\nApplied to disclosure.json");
+    }
+    out
+}
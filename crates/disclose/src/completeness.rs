@@ -0,0 +1,235 @@
+use std::collections::HashSet;
+
+use crate::errors::ValidationError;
+use crate::manifest::{ProofCompleteness, ProofItem, ProofKind, ProofSlot};
+use crate::templates::{close_matches, Template};
+
+const OPTIONAL_MARKER: &str = "(optional)";
+const STOPWORDS: &[&str] = &["and", "for", "the", "with", "optional"];
+
+/// A checklist entry derived from one `recommended_proof` string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecommendedSlot {
+    pub key: String,
+    pub label: String,
+    pub optional: bool,
+}
+
+/// Stable key for a recommended proof, e.g. `Pre-AI commit hash` becomes
+/// `pre-ai-commit-hash`. The `(optional)` marker is not part of the key.
+pub fn slot_key(label: &str) -> String {
+    let lower = label.to_lowercase().replace(OPTIONAL_MARKER, "");
+    lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub fn recommended_slots(template: &Template) -> Vec<RecommendedSlot> {
+    template
+        .recommended_proof
+        .iter()
+        .map(|label| RecommendedSlot {
+            key: slot_key(label),
+            label: label.clone(),
+            optional: label.to_lowercase().contains(OPTIONAL_MARKER),
+        })
+        .collect()
+}
+
+/// Resolves a `--slot` value given either as a key or as the label text.
+pub fn resolve_slot(template: &Template, input: &str) -> Result<String, ValidationError> {
+    let slots = recommended_slots(template);
+    let key = slot_key(input);
+    if slots.iter().any(|slot| slot.key == key) {
+        return Ok(key);
+    }
+    let keys: Vec<&str> = slots.iter().map(|slot| slot.key.as_str()).collect();
+    let hint = match close_matches(&key, &keys) {
+        close if !close.is_empty() => format!("Did you mean: {}?", close.join(", ")),
+        _ if keys.is_empty() => format!("Template {} has no recommended proof", template.slug),
+        _ => format!("Available slots: {}", keys.join(", ")),
+    };
+    Err(ValidationError::new(format!(
+        "Unknown proof slot {}. {}",
+        input, hint
+    )))
+}
+
+fn tokens(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.len() >= 3 && !STOPWORDS.contains(word))
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Words implied by the kind of proof rather than its name, so an untitled
+/// audio file still lands on "Voice memo" and a commit on "commit hash".
+fn kind_hints(item: &ProofItem) -> HashSet<String> {
+    let mime = item.mime.as_deref().unwrap_or_default();
+    let hints: &[&str] = match (&item.kind, mime) {
+        (ProofKind::GitCommit, _) => &["commit", "git", "repo", "hash"],
+        (_, mime) if mime.starts_with("audio/") => &["audio", "voice", "memo", "recording"],
+        (_, mime) if mime.starts_with("video/") => &["video", "recording"],
+        (_, mime) if mime.starts_with("image/") => &["image", "screenshot", "sketch", "photo"],
+        (_, "application/zip") => &["zip", "snapshot", "archive"],
+        _ => &[],
+    };
+    hints.iter().map(|hint| hint.to_string()).collect()
+}
+
+fn overlap(a: &HashSet<String>, b: &HashSet<String>) -> usize {
+    a.iter()
+        .filter(|word| {
+            b.iter().any(|other| {
+                word == &other
+                    || (word.len() >= 4
+                        && other.len() >= 4
+                        && (word.starts_with(other.as_str()) || other.starts_with(word.as_str())))
+            })
+        })
+        .count()
+}
+
+/// Best recommended slot for an item without an explicit `slot`, based on
+/// its label and file name, with the MIME type or proof kind as a weaker cue.
+pub fn guess_slot<'a>(item: &ProofItem, slots: &'a [RecommendedSlot]) -> Option<&'a str> {
+    let mut words = tokens(&item.label);
+    if let (ProofKind::File, Some(path)) = (&item.kind, &item.path) {
+        words.extend(tokens(path));
+    }
+    let hints = kind_hints(item);
+    let mut best: Option<(usize, &str)> = None;
+    for slot in slots {
+        let slot_words = tokens(&slot.label);
+        let score = 2 * overlap(&slot_words, &words) + overlap(&slot_words, &hints);
+        if score > 0 && best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, slot.key.as_str()));
+        }
+    }
+    best.map(|(_, key)| key)
+}
+
+/// Matches proof items to the template's recommended proof. Explicit slots
+/// win; anything else is placed by [`guess_slot`].
+pub fn compute_completeness(template: &Template, items: &[ProofItem]) -> ProofCompleteness {
+    let recommended = recommended_slots(template);
+    let mut slots: Vec<ProofSlot> = recommended
+        .iter()
+        .map(|slot| ProofSlot {
+            key: slot.key.clone(),
+            label: slot.label.clone(),
+            optional: slot.optional,
+            proof_ids: Vec::new(),
+            extra: Default::default(),
+        })
+        .collect();
+    for item in items {
+        let explicit = item
+            .slot
+            .as_deref()
+            .filter(|key| recommended.iter().any(|slot| slot.key == *key));
+        let Some(key) = explicit.or_else(|| guess_slot(item, &recommended)) else {
+            continue;
+        };
+        if let Some(slot) = slots.iter_mut().find(|slot| slot.key == key) {
+            slot.proof_ids.push(item.id.clone());
+        }
+    }
+
+    let required: Vec<&ProofSlot> = slots.iter().filter(|slot| !slot.optional).collect();
+    let counted = if required.is_empty() {
+        slots.iter().collect()
+    } else {
+        required
+    };
+    let score_percent = if counted.is_empty() {
+        100
+    } else {
+        let present = counted
+            .iter()
+            .filter(|slot| !slot.proof_ids.is_empty())
+            .count();
+        ((present * 100 + counted.len() / 2) / counted.len()) as u8
+    };
+    ProofCompleteness {
+        score_percent,
+        slots,
+        extra: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::TemplateSource;
+
+    fn template() -> Template {
+        Template {
+            slug: "article".to_string(),
+            version: "1.0.0".to_string(),
+            label: "Article".to_string(),
+            recommended_proof: vec![
+                "Raw notes".to_string(),
+                "Outline".to_string(),
+                "Early draft".to_string(),
+                "Voice memo (optional)".to_string(),
+            ],
+            stages: Vec::new(),
+            grades: None,
+            source: TemplateSource::Builtin,
+        }
+    }
+
+    fn item(id: &str, label: &str, mime: Option<&str>, slot: Option<&str>) -> ProofItem {
+        ProofItem {
+            id: id.to_string(),
+            label: label.to_string(),
+            kind: ProofKind::File,
+            path: Some(label.to_string()),
+            mime: mime.map(|m| m.to_string()),
+            size_bytes: None,
+            sha256: "0".repeat(64),
+            created_before_ai: None,
+            notes: None,
+            git: None,
+            slot: slot.map(|s| s.to_string()),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn slot_keys_drop_optional_marker() {
+        assert_eq!(slot_key("Pre-AI commit hash"), "pre-ai-commit-hash");
+        assert_eq!(slot_key("Voice memo (optional)"), "voice-memo");
+        assert_eq!(
+            resolve_slot(&template(), "Early draft").unwrap(),
+            "early-draft"
+        );
+        assert!(resolve_slot(&template(), "early-drat").is_err());
+    }
+
+    #[test]
+    fn completeness_uses_explicit_slots_and_heuristics() {
+        let items = vec![
+            item("a", "notes.md", Some("text/markdown"), None),
+            item("b", "scan.pdf", Some("application/pdf"), Some("outline")),
+            item("c", "recording.m4a", Some("audio/mp4"), None),
+            item("d", "cover.png", Some("image/png"), None),
+        ];
+        let completeness = compute_completeness(&template(), &items);
+        let ids: Vec<Vec<String>> = completeness
+            .slots
+            .iter()
+            .map(|slot| slot.proof_ids.clone())
+            .collect();
+        assert_eq!(ids[0], vec!["a"]);
+        assert_eq!(ids[1], vec!["b"]);
+        assert!(ids[2].is_empty());
+        assert_eq!(ids[3], vec!["c"]);
+        // The optional voice memo does not count towards the score.
+        assert_eq!(completeness.score_percent, 67);
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ValidationError;
+use crate::paths::user_config_dir;
+use crate::templates::close_matches;
+
+/// A setting that can come from a config file or the environment instead of
+/// a command-line flag.
+pub struct ConfigKey {
+    pub key: &'static str,
+    pub description: &'static str,
+}
+
+pub const CONFIG_KEYS: &[ConfigKey] = &[
+    ConfigKey {
+        key: "init.template",
+        description: "template slug for `init`",
+    },
+    ConfigKey {
+        key: "init.author",
+        description: "author for `init`",
+    },
+    ConfigKey {
+        key: "stamp.calendars",
+        description: "comma-separated OpenTimestamps calendar URLs",
+    },
+    ConfigKey {
+        key: "stamp.timeout",
+        description: "stamp timeout in seconds",
+    },
+    ConfigKey {
+        key: "export.bundle",
+        description: "bundle path for `export`",
+    },
+    ConfigKey {
+        key: "export.include_proof",
+        description: "none|hashes|copies",
+    },
+    ConfigKey {
+        key: "export.include_receipts",
+        description: "yes|no",
+    },
+    ConfigKey {
+        key: "export.format",
+        description: "zip|dir",
+    },
+    ConfigKey {
+        key: "publish.endpoint",
+        description: "publish API endpoint",
+    },
+    ConfigKey {
+        key: "publish.include_receipts",
+        description: "yes|no",
+    },
+    ConfigKey {
+        key: "publish.timeout",
+        description: "seconds to wait for each publish request",
+    },
+    ConfigKey {
+        key: "publish.retries",
+        description: "retries after a server error or failed connection",
+    },
+    ConfigKey {
+        key: "publish.target",
+        description: "api|static|s3",
+    },
+    ConfigKey {
+        key: "publish.site_dir",
+        description: "directory the static target writes to",
+    },
+    ConfigKey {
+        key: "publish.base_url",
+        description: "public URL of the static site or bucket",
+    },
+    ConfigKey {
+        key: "publish.s3_bucket",
+        description: "bucket for the s3 target",
+    },
+    ConfigKey {
+        key: "publish.s3_endpoint",
+        description: "S3-compatible endpoint (default: AWS for the region)",
+    },
+    ConfigKey {
+        key: "publish.s3_region",
+        description: "region for request signing (default: us-east-1)",
+    },
+    ConfigKey {
+        key: "publish.s3_prefix",
+        description: "key prefix for published objects",
+    },
+];
+
+/// Checks that `key` is a known setting, suggesting close matches otherwise.
+pub fn config_key(key: &str) -> Result<&'static ConfigKey, ValidationError> {
+    if let Some(known) = CONFIG_KEYS.iter().find(|known| known.key == key) {
+        return Ok(known);
+    }
+    let keys: Vec<&str> = CONFIG_KEYS.iter().map(|known| known.key).collect();
+    let hint = match close_matches(key, &keys) {
+        close if !close.is_empty() => format!("Did you mean: {}?", close.join(", ")),
+        _ => format!("Known keys: {}", keys.join(", ")),
+    };
+    Err(ValidationError::new(format!(
+        "Unknown config key {}. {}",
+        key, hint
+    )))
+}
+
+/// Name of the environment variable for a key, e.g. `publish.endpoint` is
+/// `DISCLOSE_PUBLISH_ENDPOINT`.
+pub fn env_var(key: &str) -> String {
+    format!("DISCLOSE_{}", key.replace('.', "_").to_uppercase())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigSource {
+    User { path: PathBuf },
+    Workspace { path: PathBuf },
+    Env { var: String },
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::User { path } => write!(f, "user ({})", path.display()),
+            ConfigSource::Workspace { path } => write!(f, "workspace ({})", path.display()),
+            ConfigSource::Env { var } => write!(f, "env ({})", var),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigValue {
+    pub key: String,
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+/// Settings merged from, lowest first: the user config file, the workspace
+/// `.disclose/config.toml` and `DISCLOSE_*` environment variables.
+/// Command-line flags override all of them.
+#[derive(Debug, Default)]
+pub struct Config {
+    values: BTreeMap<String, ConfigValue>,
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("config.toml"))
+}
+
+pub fn workspace_config_path(root: &Path) -> PathBuf {
+    root.join(".disclose").join("config.toml")
+}
+
+fn read_table(path: &Path) -> Result<toml::Table> {
+    if !path.is_file() {
+        return Ok(toml::Table::new());
+    }
+    let data = fs::read_to_string(path)?;
+    data.parse::<toml::Table>()
+        .map_err(|err| ValidationError::new(format!("Invalid {}: {}", path.display(), err)).into())
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let (section, name) = key.split_once('.')?;
+    table.get(section)?.as_table()?.get(name)
+}
+
+fn value_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        toml::Value::Array(items) => items.iter().map(value_string).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+impl Config {
+    pub fn load(workspace_root: Option<&Path>) -> Result<Self> {
+        let mut config = Config::default();
+        if let Some(path) = user_config_path() {
+            config.merge_file(&path, ConfigSource::User { path: path.clone() })?;
+        }
+        if let Some(root) = workspace_root {
+            let path = workspace_config_path(root);
+            config.merge_file(&path, ConfigSource::Workspace { path: path.clone() })?;
+        }
+        for known in CONFIG_KEYS {
+            let var = env_var(known.key);
+            if let Ok(value) = std::env::var(&var) {
+                config.insert(known.key, value, ConfigSource::Env { var });
+            }
+        }
+        Ok(config)
+    }
+
+    fn merge_file(&mut self, path: &Path, source: ConfigSource) -> Result<()> {
+        let table = read_table(path)?;
+        for known in CONFIG_KEYS {
+            if let Some(value) = lookup(&table, known.key) {
+                self.insert(known.key, value_string(value), source.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, key: &str, value: String, source: ConfigSource) {
+        self.values.insert(
+            key.to_string(),
+            ConfigValue {
+                key: key.to_string(),
+                value,
+                source,
+            },
+        );
+    }
+
+    pub fn entry(&self, key: &str) -> Option<&ConfigValue> {
+        self.values.get(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.entry(key).map(|entry| entry.value.clone())
+    }
+
+    /// Parses a value with the same `FromStr` as its flag; errors name the
+    /// layer the bad value came from.
+    pub fn parsed<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: std::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        let Some(entry) = self.entry(key) else {
+            return Ok(None);
+        };
+        entry.value.parse::<T>().map(Some).map_err(|err| {
+            ValidationError::new(format!(
+                "Invalid {} = {} from {}: {}",
+                key, entry.value, entry.source, err
+            ))
+            .into()
+        })
+    }
+
+    /// Every set value, in the order of [`CONFIG_KEYS`].
+    pub fn entries(&self) -> Vec<&ConfigValue> {
+        CONFIG_KEYS
+            .iter()
+            .filter_map(|known| self.entry(known.key))
+            .collect()
+    }
+}
+
+/// Sets (or with `None` removes) `key` in the config file at `path`,
+/// keeping everything else in the file.
+pub fn write_config_value(path: &Path, key: &str, value: Option<toml::Value>) -> Result<()> {
+    let mut table = read_table(path)?;
+    let (section, name) = key
+        .split_once('.')
+        .ok_or_else(|| anyhow!("Config keys have the form section.name"))?;
+    let entry = table
+        .entry(section.to_string())
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    let section_table = entry.as_table_mut().ok_or_else(|| {
+        ValidationError::new(format!("{} in {} is not a table", section, path.display()))
+    })?;
+    match value {
+        Some(value) => {
+            section_table.insert(name.to_string(), value);
+        }
+        None => {
+            section_table.remove(name);
+            if section_table.is_empty() {
+                table.remove(section);
+            }
+        }
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, toml::to_string_pretty(&table)?)?;
+    Ok(())
+}
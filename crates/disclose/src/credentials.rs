@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ValidationError;
+use crate::paths::user_config_dir;
+
+/// Used when no token is stored for the endpoint.
+pub const TOKEN_ENV: &str = "DISCLOSE_TOKEN";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredToken {
+    pub token: String,
+    pub saved_at: String,
+}
+
+/// Publish tokens per endpoint, kept in `credentials.json` in the user config
+/// directory and readable by the owner only.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Credentials {
+    #[serde(default)]
+    pub tokens: BTreeMap<String, StoredToken>,
+}
+
+pub fn credentials_path() -> Result<PathBuf> {
+    user_config_dir()
+        .map(|dir| dir.join("credentials.json"))
+        .ok_or_else(|| anyhow!("No user config directory on this system"))
+}
+
+/// Key a token is stored under: the endpoint without trailing slashes, with
+/// scheme and host lowercased.
+pub fn endpoint_key(endpoint: &str) -> Result<String> {
+    let trimmed = endpoint.trim().trim_end_matches('/');
+    let (scheme, rest) = trimmed
+        .split_once("://")
+        .filter(|(scheme, rest)| {
+            matches!(scheme.to_lowercase().as_str(), "http" | "https") && !rest.is_empty()
+        })
+        .ok_or_else(|| {
+            ValidationError::new(format!(
+                "Invalid endpoint {}, expected an http(s) URL",
+                endpoint
+            ))
+        })?;
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let mut key = format!("{}://{}", scheme.to_lowercase(), host.to_lowercase());
+    if !path.is_empty() {
+        key.push('/');
+        key.push_str(path);
+    }
+    Ok(key)
+}
+
+impl Credentials {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(|err| anyhow!("Invalid {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        // The mode above only applies to new files.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+pub fn save_token(endpoint: &str, token: &str) -> Result<PathBuf> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(ValidationError::new("Token must not be empty").into());
+    }
+    let path = credentials_path()?;
+    let mut credentials = Credentials::load(&path)?;
+    credentials.tokens.insert(
+        endpoint_key(endpoint)?,
+        StoredToken {
+            token: token.to_string(),
+            saved_at: Utc::now().to_rfc3339(),
+        },
+    );
+    credentials.save(&path)?;
+    Ok(path)
+}
+
+/// Removes the token for `endpoint`, or every token with `None`. Returns the
+/// endpoints that were removed.
+pub fn remove_tokens(endpoint: Option<&str>) -> Result<Vec<String>> {
+    let path = credentials_path()?;
+    let mut credentials = Credentials::load(&path)?;
+    let removed = match endpoint {
+        Some(endpoint) => {
+            let key = endpoint_key(endpoint)?;
+            credentials
+                .tokens
+                .remove(&key)
+                .map(|_| vec![key])
+                .unwrap_or_default()
+        }
+        None => std::mem::take(&mut credentials.tokens)
+            .into_keys()
+            .collect(),
+    };
+    if !removed.is_empty() {
+        credentials.save(&path)?;
+    }
+    Ok(removed)
+}
+
+/// Token for publishing to `endpoint`: the stored one, then `DISCLOSE_TOKEN`.
+pub fn resolve_token(endpoint: &str) -> Result<Option<String>> {
+    let credentials = Credentials::load(&credentials_path()?)?;
+    if let Some(stored) = credentials.tokens.get(&endpoint_key(endpoint)?) {
+        return Ok(Some(stored.token.clone()));
+    }
+    Ok(std::env::var(TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.trim().is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_keys_ignore_case_and_trailing_slash() {
+        assert_eq!(
+            endpoint_key("HTTPS://Disclose.Example.com/").unwrap(),
+            "https://disclose.example.com"
+        );
+        assert_eq!(
+            endpoint_key("http://localhost:3000/Base/").unwrap(),
+            "http://localhost:3000/Base"
+        );
+        assert!(endpoint_key("disclose.example.com").is_err());
+    }
+}
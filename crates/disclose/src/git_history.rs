@@ -0,0 +1,310 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Command;
+
+use crate::templates::Template;
+
+/// Stages a changed path can be attributed to, in the keys of the `code`
+/// template.
+pub const GIT_STAGES: &[&str] = &["implementation", "testing", "docs"];
+
+/// Names and emails of assistants that commonly show up in commit trailers
+/// or as commit authors.
+const AI_MARKERS: &[&str] = &[
+    "claude",
+    "anthropic",
+    "copilot",
+    "chatgpt",
+    "openai",
+    "aider",
+    "cursor",
+    "codex",
+    "gemini",
+    "devin",
+    "codeium",
+    "windsurf",
+];
+
+/// Opens the analysis paragraph in `assistance.notes`, so a later run can
+/// replace it.
+pub const NOTES_PREFIX: &str = "From git history:";
+
+/// Message tags that mark a commit as AI-assisted.
+const AI_TAGS: &[&str] = &[
+    "[ai]",
+    "[ai-assisted]",
+    "ai-assisted",
+    "ai-generated",
+    "(aider)",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct CommitStats {
+    pub author: String,
+    pub ai_co_authors: Vec<String>,
+    pub ai_tagged: bool,
+    /// `(path, lines added + deleted)`; binary files are skipped.
+    pub changes: Vec<(String, u64)>,
+}
+
+impl CommitStats {
+    pub fn ai_assisted(&self) -> bool {
+        !self.ai_co_authors.is_empty() || self.ai_tagged
+    }
+}
+
+fn mentions_ai(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    AI_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+fn read_message(commit: &mut CommitStats, message: &str) {
+    for line in message.lines() {
+        let trimmed = line.trim();
+        let lower = trimmed.to_lowercase();
+        if lower.starts_with("co-authored-by:") {
+            let co_author = trimmed["co-authored-by:".len()..].trim();
+            if mentions_ai(co_author) {
+                let name = co_author
+                    .split('<')
+                    .next()
+                    .unwrap_or(co_author)
+                    .trim()
+                    .to_string();
+                if !commit.ai_co_authors.contains(&name) {
+                    commit.ai_co_authors.push(name);
+                }
+            }
+        } else if AI_TAGS.iter().any(|tag| lower.contains(tag))
+            || ((lower.contains("generated with") || lower.contains("generated by"))
+                && mentions_ai(&lower))
+        {
+            commit.ai_tagged = true;
+        }
+    }
+    // aider commits as "<name> (aider)" unless configured otherwise.
+    if commit.author.to_lowercase().ends_with("(aider)") {
+        commit.ai_tagged = true;
+    }
+}
+
+/// Parses `git log --numstat --format=%x1e%an%x1f%B%x1f` output.
+pub fn parse_log(output: &str) -> Vec<CommitStats> {
+    output
+        .split('\u{1e}')
+        .filter(|record| !record.trim().is_empty())
+        .map(|record| {
+            let mut fields = record.splitn(3, '\u{1f}');
+            let mut commit = CommitStats {
+                author: fields.next().unwrap_or_default().trim().to_string(),
+                ..Default::default()
+            };
+            read_message(&mut commit, fields.next().unwrap_or_default());
+            for line in fields.next().unwrap_or_default().lines() {
+                let mut columns = line.splitn(3, '\t');
+                let (Some(added), Some(deleted), Some(path)) =
+                    (columns.next(), columns.next(), columns.next())
+                else {
+                    continue;
+                };
+                if let (Ok(added), Ok(deleted)) = (added.parse::<u64>(), deleted.parse::<u64>()) {
+                    commit.changes.push((path.to_string(), added + deleted));
+                }
+            }
+            commit
+        })
+        .collect()
+}
+
+pub fn read_git_log(repo: &Path) -> Result<Vec<CommitStats>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "log",
+            "--no-merges",
+            "--numstat",
+            "--format=%x1e%an%x1f%B%x1f",
+        ])
+        .output()
+        .map_err(|err| anyhow!("Failed to run git: {}", err))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Maps a changed path to `testing`, `docs` or `implementation`.
+pub fn path_stage(path: &str) -> &'static str {
+    let lower = path.to_lowercase();
+    let file = lower.rsplit('/').next().unwrap_or_default();
+    let in_dir = |dir: &str| {
+        lower.starts_with(&format!("{}/", dir)) || lower.contains(&format!("/{}/", dir))
+    };
+    if ["test", "tests", "spec", "__tests__"]
+        .iter()
+        .any(|dir| in_dir(dir))
+        || file.starts_with("test_")
+        || file.contains("_test.")
+        || file.contains(".test.")
+        || file.contains(".spec.")
+    {
+        "testing"
+    } else if ["docs", "doc"].iter().any(|dir| in_dir(dir))
+        || [".md", ".mdx", ".rst", ".adoc"]
+            .iter()
+            .any(|ext| file.ends_with(ext))
+        || file.starts_with("readme")
+        || file.starts_with("changelog")
+    {
+        "docs"
+    } else {
+        "implementation"
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitStageStats {
+    pub key: String,
+    pub lines: u64,
+    pub ai_lines: u64,
+    pub ai_percent: i32,
+    pub grade: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitAnalysis {
+    pub commits: usize,
+    pub ai_commits: usize,
+    pub co_authored_commits: usize,
+    pub tagged_commits: usize,
+    pub ai_co_authors: Vec<String>,
+    pub lines: u64,
+    pub ai_lines: u64,
+    pub ai_percent: i32,
+    pub stages: Vec<GitStageStats>,
+}
+
+fn percent(part: u64, total: u64) -> i32 {
+    (part * 100 + total / 2).checked_div(total).unwrap_or(0) as i32
+}
+
+/// AI share of changed lines overall and per stage. A commit counts as AI
+/// assisted when it has an assistant `Co-authored-by` trailer or an AI tag;
+/// stages the template does not have are left out.
+pub fn analyze_commits(template: &Template, commits: &[CommitStats]) -> GitAnalysis {
+    let keys: Vec<&str> = GIT_STAGES
+        .iter()
+        .copied()
+        .filter(|key| template.stages.iter().any(|stage| stage.key == *key))
+        .collect();
+    let mut totals = vec![(0u64, 0u64); keys.len()];
+    let mut ai_co_authors: Vec<String> = Vec::new();
+    let (mut lines, mut ai_lines) = (0, 0);
+    for commit in commits {
+        for name in &commit.ai_co_authors {
+            if !ai_co_authors.contains(name) {
+                ai_co_authors.push(name.clone());
+            }
+        }
+        for (path, changed) in &commit.changes {
+            lines += changed;
+            let ai = if commit.ai_assisted() { *changed } else { 0 };
+            ai_lines += ai;
+            if let Some(index) = keys.iter().position(|key| *key == path_stage(path)) {
+                totals[index].0 += changed;
+                totals[index].1 += ai;
+            }
+        }
+    }
+    let stages = keys
+        .iter()
+        .zip(totals)
+        .filter(|(_, (lines, _))| *lines > 0)
+        .map(|(key, (lines, ai_lines))| {
+            let ai_percent = percent(ai_lines, lines);
+            GitStageStats {
+                key: key.to_string(),
+                lines,
+                ai_lines,
+                ai_percent,
+                grade: template.grade_for_percent(ai_percent).key,
+            }
+        })
+        .collect();
+    GitAnalysis {
+        commits: commits.len(),
+        ai_commits: commits.iter().filter(|commit| commit.ai_assisted()).count(),
+        co_authored_commits: commits
+            .iter()
+            .filter(|commit| !commit.ai_co_authors.is_empty())
+            .count(),
+        tagged_commits: commits.iter().filter(|commit| commit.ai_tagged).count(),
+        ai_co_authors,
+        lines,
+        ai_lines,
+        ai_percent: percent(ai_lines, lines),
+        stages,
+    }
+}
+
+impl GitAnalysis {
+    /// Plain-language account of the numbers, stored in `assistance.notes`.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "{} {} of {} commits AI-assisted ({} with Co-authored-by trailers, {} tagged)",
+            NOTES_PREFIX,
+            self.ai_commits,
+            self.commits,
+            self.co_authored_commits,
+            self.tagged_commits
+        );
+        if !self.ai_co_authors.is_empty() {
+            let _ = write!(out, "; co-authors: {}", self.ai_co_authors.join(", "));
+        }
+        let _ = write!(
+            out,
+            ". AI share of changed lines: {}% of {}",
+            self.ai_percent, self.lines
+        );
+        for stage in &self.stages {
+            let _ = write!(
+                out,
+                ", {} {}% of {} ({})",
+                stage.key, stage.ai_percent, stage.lines, stage.grade
+            );
+        }
+        out.push('.');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_map_to_code_stages() {
+        assert_eq!(path_stage("src/lib.rs"), "implementation");
+        assert_eq!(path_stage("tests/e2e_cli.rs"), "testing");
+        assert_eq!(path_stage("web/app/page.test.tsx"), "testing");
+        assert_eq!(path_stage("docs/CLI_SPEC.md"), "docs");
+        assert_eq!(path_stage("README.md"), "docs");
+    }
+
+    #[test]
+    fn trailers_and_tags_mark_ai_commits() {
+        let log = "\u{1e}Ada\u{1f}Add parser\n\nCo-authored-by: Claude <noreply@anthropic.com>\nCo-authored-by: Bob <bob@example.com>\n\u{1f}\n10\t2\tsrc/parser.rs\n-\t-\tlogo.png\n\u{1e}Ada (aider)\u{1f}fix tests\n\u{1f}\n4\t0\ttests/parser.rs\n\u{1e}Ada\u{1f}Write docs\n\u{1f}\n6\t0\tREADME.md\n";
+        let commits = parse_log(log);
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[0].ai_co_authors, vec!["Claude"]);
+        assert_eq!(commits[0].changes, vec![("src/parser.rs".to_string(), 12)]);
+        assert!(commits[1].ai_tagged);
+        assert!(!commits[2].ai_assisted());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::manifest::DisclosureManifest;
+use crate::manifest::{DisclosureManifest, PathPolicy};
 use crate::merkle::bundle_root_hex;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
@@ -69,10 +69,29 @@ fn canonicalize_value(value: &Value) -> Value {
 }
 
 pub fn stable_manifest_string(manifest: &DisclosureManifest) -> Result<String> {
-    let mut value = serde_json::to_value(manifest)?;
+    stable_value_string(serde_json::to_value(manifest)?)
+}
+
+/// Canonical hashing payload for a manifest held as raw JSON, e.g. exactly as
+/// stored on disk before any version migration.
+///
+/// Proof paths are hashed the way `proof.path_policy` publishes them, so the
+/// workspace manifest and its redacted export share one manifest hash and
+/// the same receipts verify both.
+pub fn stable_value_string(mut value: Value) -> Result<String> {
     if let Some(proof) = value.get_mut("proof") {
         if let Some(obj) = proof.as_object_mut() {
             obj.remove("bundle_root_sha256");
+            let policy = obj
+                .get("path_policy")
+                .and_then(|policy| PathPolicy::deserialize(policy).ok())
+                .unwrap_or_default();
+            let items = obj.get_mut("items").and_then(Value::as_array_mut);
+            for item in items.into_iter().flatten() {
+                if let Some(Value::String(path)) = item.get_mut("path") {
+                    *path = policy.apply(path);
+                }
+            }
         }
     }
     if let Some(obj) = value.as_object_mut() {
@@ -88,8 +107,24 @@ pub fn manifest_hash(manifest: &DisclosureManifest) -> Result<String> {
     Ok(sha256_hex_bytes(payload.as_bytes()))
 }
 
+pub fn manifest_hash_value(value: &Value) -> Result<String> {
+    let payload = stable_value_string(value.clone())?;
+    Ok(sha256_hex_bytes(payload.as_bytes()))
+}
+
 pub fn build_hashes(manifest: &DisclosureManifest) -> Result<HashesJson> {
     let manifest_sha = manifest_hash(manifest)?;
+    build_hashes_with(manifest, manifest_sha)
+}
+
+/// Hashes for a manifest as stored on disk. Receipts were stamped over the
+/// stored bytes, so verification must not hash a migrated copy.
+pub fn build_stored_hashes(stored: &Value, manifest: &DisclosureManifest) -> Result<HashesJson> {
+    let manifest_sha = manifest_hash_value(stored)?;
+    build_hashes_with(manifest, manifest_sha)
+}
+
+fn build_hashes_with(manifest: &DisclosureManifest, manifest_sha: String) -> Result<HashesJson> {
     let proof_hashes: Vec<String> = manifest
         .proof
         .items
@@ -108,7 +143,10 @@ pub fn build_hashes(manifest: &DisclosureManifest) -> Result<HashesJson> {
             id: item.id.clone(),
             sha256: item.sha256.clone(),
             size_bytes: item.size_bytes,
-            path: item.path.clone(),
+            path: item
+                .path
+                .as_deref()
+                .map(|path| manifest.path_policy().apply(path)),
         })
         .collect();
 
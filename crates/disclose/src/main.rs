@@ -1,25 +1,52 @@
 use anyhow::{anyhow, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use serde_json::json;
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
+use std::time::Duration;
 
 mod commands;
+mod completeness;
+mod config;
+mod credentials;
 mod errors;
+mod git_history;
 mod hashing;
 mod manifest;
 mod merkle;
+mod migrations;
 mod ots;
+mod paths;
+mod pii;
 mod publish;
 mod templates;
 mod tui;
+mod usage;
 mod validation;
+mod version;
 mod workspace;
 
 use commands::{
-    attach_proof, export_bundle, info_receipt, init_workspace, publish_workspace, stamp_workspace,
-    update_meter, upgrade_receipt, verify_receipt, ExportFormat, IncludeProof,
+    add_tool, analyze_git_history, attach_proof, export_bundle, fetch_workspace, format_checklist,
+    format_config_list, format_config_value, format_fetch_report, format_git_proposal,
+    format_project_report, format_publish_plan, format_status, format_template,
+    format_template_lint, format_template_list, format_template_upgrade, format_tool_list,
+    format_usage_import, get_config, import_usage, info_receipt, init_workspace, lint_template,
+    list_templates, list_tools, meter_workspace, migrate_workspace, parse_fetch_source,
+    parse_scope, plan_publish, remove_tool, scan_export, send_publish_plan, set_config,
+    set_project, show_template, stamp_workspace, unpublish_workspace, unset_config,
+    upgrade_receipt, upgrade_template, verify_receipt, workspace_status, ExportFormat,
+    IncludeProof, MeterChanges, ProjectUpdate, ScopeChange, ScopeUpdate, SplitMode,
+    DEFAULT_SPLIT_TOLERANCE,
 };
+use config::Config;
 use errors::ValidationError;
+use manifest::PathPolicy;
+use pii::{ensure_allowed, format_findings, Finding};
+use publish::{
+    ApiTarget, HttpOptions, PublishError, PublishTarget, S3Credentials, S3Settings, S3Target,
+    StaticSiteTarget, TargetKind,
+};
 use workspace::Workspace;
 
 #[derive(Parser)]
@@ -27,6 +54,8 @@ use workspace::Workspace;
 struct Cli {
     #[arg(global = true, long, value_name = "DIR")]
     path: Option<PathBuf>,
+    #[arg(global = true, long, value_name = "NAME")]
+    disclosure: Option<String>,
     #[arg(global = true, long)]
     json: bool,
     #[arg(global = true, long)]
@@ -45,15 +74,21 @@ struct Cli {
 enum Commands {
     Init {
         #[arg(long)]
-        template: String,
-        #[arg(long)]
-        title: String,
+        template: Option<String>,
+        #[arg(long, value_name = "PATH")]
+        template_file: Option<PathBuf>,
+        #[arg(long, required_unless_present = "from")]
+        title: Option<String>,
         #[arg(long)]
         author: Option<String>,
         #[arg(long, default_value = "./disclose")]
         out: PathBuf,
         #[arg(long)]
         link: Vec<String>,
+        #[arg(long)]
+        force: bool,
+        #[arg(long, value_name = "DISCLOSURE_JSON")]
+        from: Option<PathBuf>,
     },
     Attach {
         #[arg(long, value_name = "PATH")]
@@ -72,6 +107,8 @@ enum Commands {
         git: Option<String>,
         #[arg(long)]
         commit: Option<String>,
+        #[arg(long, value_name = "SLOT")]
+        slot: Option<String>,
     },
     Meter {
         #[arg(long)]
@@ -82,6 +119,27 @@ enum Commands {
         stage: Vec<String>,
         #[arg(long)]
         allow_unknown_stages: bool,
+        #[arg(long, value_name = "warn|reject|derive", default_value = "warn")]
+        split_mode: SplitMode,
+        #[arg(long, value_name = "POINTS", default_value_t = DEFAULT_SPLIT_TOLERANCE)]
+        split_tolerance: i32,
+        #[arg(long, value_name = "KIND:VALUE", conflicts_with = "remove_scope")]
+        scope: Option<String>,
+        #[arg(long, value_name = "0..100", requires = "scope")]
+        scope_ai: Option<i32>,
+        #[arg(long, value_name = "0..100", requires = "scope")]
+        scope_human: Option<i32>,
+        #[arg(long, value_name = "GRADE", requires = "scope")]
+        scope_grade: Option<String>,
+        #[arg(long, value_name = "TEXT", requires = "scope")]
+        scope_note: Option<String>,
+        #[arg(long, value_name = "KIND:VALUE")]
+        remove_scope: Option<String>,
+        #[arg(long, value_name = "REPO", conflicts_with_all = ["scope", "remove_scope"])]
+        from_git: Option<PathBuf>,
+        /// Write the --from-git proposal instead of only showing it
+        #[arg(long, requires = "from_git")]
+        apply: bool,
     },
     Stamp {
         #[arg(long)]
@@ -118,33 +176,337 @@ enum Commands {
         receipt: Option<PathBuf>,
     },
     Export {
-        #[arg(long, default_value = "disclosure-bundle.zip")]
-        bundle: PathBuf,
-        #[arg(long, default_value = "hashes")]
-        include_proof: IncludeProof,
-        #[arg(long, default_value = "yes")]
-        include_receipts: String,
-        #[arg(long, default_value = "zip")]
-        format: ExportFormat,
+        #[arg(long, value_name = "PATH")]
+        bundle: Option<PathBuf>,
+        #[arg(long, value_name = "none|hashes|copies")]
+        include_proof: Option<IncludeProof>,
+        #[arg(long, value_name = "yes|no")]
+        include_receipts: Option<String>,
+        #[arg(long, value_name = "zip|dir")]
+        format: Option<ExportFormat>,
+        /// How proof paths appear in the bundle (default: the manifest's policy)
+        #[arg(long, value_name = "relative|basename|hash")]
+        path_policy: Option<PathPolicy>,
+        /// Export even if personal data or secrets were found
+        #[arg(long)]
+        allow_sensitive: bool,
     },
-    Publish {
+    Publish(PublishArgs),
+    /// Download a publication into a read-only workspace and verify it
+    Fetch {
+        /// Publication URL or slug
+        source: String,
         #[arg(long)]
-        endpoint: String,
+        endpoint: Option<String>,
+        /// Folder to save into (default: ./<slug>)
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Seconds to wait for each download attempt and for receipt
+        /// verification (default: publish.timeout, else 30)
+        #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+    },
+    Unpublish {
+        #[command(flatten)]
+        target: TargetArgs,
         #[arg(long)]
         token: Option<String>,
-        #[arg(long, default_value = "yes")]
-        include_receipts: String,
+        /// Seconds to wait for each attempt (default: publish.timeout, else 30)
+        #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+        /// Retries after a server error or failed connection (default: publish.retries, else 3)
+        #[arg(long)]
+        retries: Option<u32>,
+    },
+    Login {
+        #[arg(long)]
+        endpoint: Option<String>,
+    },
+    Logout {
+        #[arg(long, conflicts_with = "all")]
+        endpoint: Option<String>,
+        #[arg(long)]
+        all: bool,
+    },
+    Status,
+    Migrate,
+    #[command(alias = "template")]
+    Templates {
+        #[command(subcommand)]
+        command: TemplatesCommand,
+    },
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommand,
+    },
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    #[command(alias = "tool")]
+    Tools {
+        #[command(subcommand)]
+        command: ToolsCommand,
+    },
+    ImportUsage {
+        #[arg(required = true, value_name = "LOG")]
+        files: Vec<PathBuf>,
+        #[arg(long)]
+        format: Option<usage::UsageFormat>,
+        #[arg(long)]
+        apply: bool,
     },
     Tui,
 }
 
-fn resolve_workspace(path: Option<PathBuf>) -> Result<Workspace> {
-    let root = path.unwrap_or(std::env::current_dir()?);
-    let workspace = Workspace::new(root);
-    if !workspace.exists() {
-        return Err(anyhow!("disclosure.json not found in workspace"));
+#[derive(Subcommand)]
+enum ConfigCommand {
+    List,
+    Get {
+        key: String,
+    },
+    Set {
+        key: String,
+        value: String,
+        #[arg(long)]
+        user: bool,
+    },
+    Unset {
+        key: String,
+        #[arg(long)]
+        user: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectCommand {
+    Set {
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        author: Option<String>,
+        #[arg(long = "add-link", value_name = "URL")]
+        add_links: Vec<String>,
+        #[arg(long = "remove-link", value_name = "URL")]
+        remove_links: Vec<String>,
+        #[arg(long, value_name = "public|employer|school|publisher|private")]
+        audience: Option<String>,
+        #[arg(long, value_name = "TEXT")]
+        assistance_notes: Option<String>,
+        /// How exports and publications show proof paths
+        #[arg(long, value_name = "relative|basename|hash")]
+        path_policy: Option<PathPolicy>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ToolsCommand {
+    List,
+    Add {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        provider: Option<String>,
+        #[arg(long)]
+        model: Option<String>,
+        #[arg(long)]
+        version: Option<String>,
+        #[arg(long)]
+        notes: Option<String>,
+        #[arg(long = "stage", value_name = "KEY")]
+        stages: Vec<String>,
+    },
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplatesCommand {
+    List,
+    Show {
+        slug: String,
+    },
+    Validate {
+        #[arg(value_name = "PATH")]
+        file: PathBuf,
+    },
+    Upgrade {
+        #[arg(long, value_name = "VERSION")]
+        to: Option<String>,
+        #[arg(long = "map", value_name = "OLD=NEW")]
+        map: Vec<String>,
+        /// Allow moving to an older version than the workspace's
+        #[arg(long)]
+        allow_downgrade: bool,
+    },
+}
+
+/// An explicit `--path` names the workspace folder itself; only the current
+/// directory is searched upwards.
+fn resolve_workspace(path: Option<PathBuf>, name: Option<&str>) -> Result<Workspace> {
+    match path {
+        Some(path) => Workspace::open(&path, name),
+        None => Workspace::discover(&std::env::current_dir()?, name),
     }
-    Ok(workspace)
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Decides how to go on when the scanner found personal data or secrets.
+/// Returns `None` when the user declines at the prompt; otherwise whether the
+/// findings are allowed. Without a terminal the command itself refuses.
+fn review_findings(
+    findings: &[Finding],
+    allow_sensitive: bool,
+    interactive: bool,
+    question: &str,
+) -> Result<Option<bool>> {
+    if findings.is_empty() {
+        return Ok(Some(allow_sensitive));
+    }
+    if allow_sensitive {
+        eprintln!("Warning: {}", format_findings(findings));
+        return Ok(Some(true));
+    }
+    if !interactive {
+        return Ok(Some(false));
+    }
+    println!("{}", format_findings(findings));
+    Ok(confirm(question)?.then_some(true))
+}
+
+#[derive(Args)]
+struct PublishArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+    #[arg(long)]
+    token: Option<String>,
+    #[arg(long, value_name = "yes|no")]
+    include_receipts: Option<String>,
+    /// How proof paths appear in the publication (default: the manifest's policy)
+    #[arg(long, value_name = "relative|basename|hash")]
+    path_policy: Option<PathPolicy>,
+    /// Publish a separate copy instead of updating the existing publication
+    #[arg(long)]
+    new: bool,
+    /// Show the request instead of sending it
+    #[arg(long)]
+    dry_run: bool,
+    /// With --dry-run, write the request body to this file
+    #[arg(long, requires = "dry_run")]
+    out: Option<PathBuf>,
+    /// Publish even if personal data or secrets were found
+    #[arg(long)]
+    allow_sensitive: bool,
+    /// Seconds to wait for each attempt (default: publish.timeout, else 30)
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+    /// Retries after a server error or failed connection (default: publish.retries, else 3)
+    #[arg(long)]
+    retries: Option<u32>,
+}
+
+/// Where `publish` and `unpublish` send a disclosure.
+#[derive(Args)]
+struct TargetArgs {
+    /// Publish target (default: publish.target, else api)
+    #[arg(long, value_name = "api|static|s3")]
+    target: Option<TargetKind>,
+    /// Server for the api target (default: publish.endpoint)
+    #[arg(long)]
+    endpoint: Option<String>,
+    /// Directory for the static target (default: publish.site_dir)
+    #[arg(long, value_name = "DIR")]
+    site_dir: Option<PathBuf>,
+    /// Bucket for the s3 target (default: publish.s3_bucket)
+    #[arg(long)]
+    bucket: Option<String>,
+    /// Public URL of the site or bucket, for publication links (default: publish.base_url)
+    #[arg(long, value_name = "URL")]
+    base_url: Option<String>,
+}
+
+/// Builds the publish target from flags, then config. S3 keys come from
+/// the environment only.
+fn publish_target(
+    args: TargetArgs,
+    config: &Config,
+    http: HttpOptions,
+) -> Result<Box<dyn PublishTarget>> {
+    let kind = match args.target {
+        Some(kind) => kind,
+        None => config.parsed("publish.target")?.unwrap_or_default(),
+    };
+    let base_url = args.base_url.or_else(|| config.get("publish.base_url"));
+    Ok(match kind {
+        TargetKind::Api => {
+            let endpoint = args
+                .endpoint
+                .or_else(|| config.get("publish.endpoint"))
+                .ok_or_else(|| {
+                    ValidationError::new("Provide --endpoint or set publish.endpoint")
+                })?;
+            Box::new(ApiTarget::new(endpoint, http))
+        }
+        TargetKind::Static => {
+            let dir = args
+                .site_dir
+                .or_else(|| config.get("publish.site_dir").map(PathBuf::from))
+                .ok_or_else(|| {
+                    ValidationError::new("Provide --site-dir or set publish.site_dir")
+                })?;
+            Box::new(StaticSiteTarget::new(dir, base_url))
+        }
+        TargetKind::S3 => {
+            let bucket = args
+                .bucket
+                .or_else(|| config.get("publish.s3_bucket"))
+                .ok_or_else(|| ValidationError::new("Provide --bucket or set publish.s3_bucket"))?;
+            let region = config
+                .get("publish.s3_region")
+                .unwrap_or_else(|| "us-east-1".to_string());
+            let endpoint = config
+                .get("publish.s3_endpoint")
+                .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+            let settings = S3Settings {
+                endpoint,
+                bucket,
+                region,
+                prefix: config.get("publish.s3_prefix").unwrap_or_default(),
+                base_url,
+            };
+            Box::new(S3Target::new(settings, S3Credentials::from_env(), http))
+        }
+    })
+}
+
+/// Request settings for the publish server: flags first, then
+/// `publish.timeout` and `publish.retries`.
+fn http_options(
+    config: &Config,
+    timeout: Option<u64>,
+    retries: Option<u32>,
+) -> Result<HttpOptions> {
+    let defaults = HttpOptions::default();
+    let timeout = match timeout {
+        Some(timeout) => Some(timeout),
+        None => config.parsed("publish.timeout")?,
+    };
+    let retries = match retries {
+        Some(retries) => Some(retries),
+        None => config.parsed("publish.retries")?,
+    };
+    Ok(HttpOptions {
+        timeout: timeout.map_or(defaults.timeout, Duration::from_secs),
+        retries: retries.unwrap_or(defaults.retries),
+    })
 }
 
 fn output_json(action: &str, path: &str, result: serde_json::Value) {
@@ -158,7 +520,12 @@ fn output_json(action: &str, path: &str, result: serde_json::Value) {
 }
 
 fn error_code(err: &anyhow::Error) -> i32 {
-    if err.downcast_ref::<ValidationError>().is_some() {
+    if let Some(err) = err.downcast_ref::<PublishError>() {
+        return err.exit_code();
+    }
+    if err.downcast_ref::<ValidationError>().is_some()
+        || err.downcast_ref::<pii::SensitiveContentError>().is_some()
+    {
         return 2;
     }
     let message = err.to_string().to_lowercase();
@@ -177,6 +544,210 @@ fn error_code(err: &anyhow::Error) -> i32 {
     1
 }
 
+/// The `--json` shape of a failure. `kind` names the exit code, or the
+/// publish failure; typed errors add their fields under `details`.
+fn error_json(err: &anyhow::Error, code: i32) -> serde_json::Value {
+    let mut kind = match code {
+        2 => "validation",
+        3 => "verification",
+        4 => "network",
+        5 => "file",
+        6 => "unauthorized",
+        _ => "error",
+    };
+    let mut details = serde_json::Value::Null;
+    if let Some(publish) = err.downcast_ref::<PublishError>() {
+        details = json!(publish);
+    } else if let Some(sensitive) = err.downcast_ref::<pii::SensitiveContentError>() {
+        kind = "sensitive_content";
+        details = json!({ "findings": sensitive.findings });
+    }
+    // Publish errors name themselves: rejected, server, timeout, ...
+    let kind = details
+        .get("kind")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or(kind)
+        .to_string();
+    json!({
+        "ok": false,
+        "error": {
+            "kind": kind,
+            "exit_code": code,
+            "message": err.to_string(),
+            "details": details
+        }
+    })
+}
+
+/// Runs `disclose publish`: plans the request, reviews findings and sends it.
+async fn run_publish(
+    args: PublishArgs,
+    dir: Option<PathBuf>,
+    disclosure: Option<&str>,
+    json: bool,
+    quiet: bool,
+    yes: bool,
+) -> Result<()> {
+    let PublishArgs {
+        target,
+        token,
+        include_receipts,
+        path_policy,
+        new,
+        dry_run,
+        out,
+        allow_sensitive,
+        timeout,
+        retries,
+    } = args;
+    let workspace = resolve_workspace(dir, disclosure)?;
+    let config = Config::load(Some(workspace.root_path()))?;
+    let target = publish_target(target, &config, http_options(&config, timeout, retries)?)?;
+    let include_receipts = include_receipts
+        .or_else(|| config.get("publish.include_receipts"))
+        .is_none_or(|value| matches!(value.as_str(), "yes" | "true"));
+    let plan = plan_publish(&workspace, &*target, include_receipts, new, path_policy)?;
+    let path = workspace.root_path().to_string_lossy().to_string();
+    if dry_run {
+        if let Some(out) = &out {
+            std::fs::write(out, &plan.body)?;
+        }
+        if json {
+            let body: serde_json::Value = serde_json::from_str(&plan.body)?;
+            output_json(
+                "publish",
+                &path,
+                json!({ "dry_run": true, "plan": plan, "body": body, "out": out }),
+            );
+        } else if let Some(out) = &out {
+            if !quiet {
+                println!("{}", format_publish_plan(&plan));
+                println!("Wrote request body to {}", out.display());
+            }
+        } else {
+            // Keep stdout to the exact body so it can be piped.
+            if !quiet {
+                eprintln!("{}", format_publish_plan(&plan));
+            }
+            println!("{}", plan.body);
+        }
+        return Ok(());
+    }
+    let interactive = !json && !quiet && std::io::stdin().is_terminal();
+    let allow_sensitive = if interactive && !yes {
+        // The plan summary lists any findings, so one answer covers both.
+        println!("{}", format_publish_plan(&plan));
+        let question = if plan.findings.is_empty() || allow_sensitive {
+            "Send this to the server?"
+        } else {
+            "Send this despite the findings above?"
+        };
+        if !confirm(question)? {
+            return Ok(());
+        }
+        true
+    } else {
+        match review_findings(
+            &plan.findings,
+            allow_sensitive,
+            interactive,
+            "Publish anyway?",
+        )? {
+            Some(allow_sensitive) => allow_sensitive,
+            None => return Ok(()),
+        }
+    };
+    let report = send_publish_plan(&workspace, &*target, token, plan, allow_sensitive).await?;
+    if json {
+        output_json(
+            "publish",
+            workspace.root_path().to_string_lossy().as_ref(),
+            json!(report),
+        );
+    } else if !quiet {
+        match report.revision.filter(|_| report.updated) {
+            Some(revision) => {
+                println!("Updated: {} (revision {})", report.url, revision)
+            }
+            None if report.updated => println!("Updated: {}", report.url),
+            None => println!("Published: {}", report.url),
+        }
+        if let Some(previous) = &report.forked_from {
+            println!("The previous publication {} is still online", previous);
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `disclose templates` subcommands.
+fn run_templates(
+    command: TemplatesCommand,
+    dir: Option<PathBuf>,
+    disclosure: Option<&str>,
+    json: bool,
+    quiet: bool,
+) -> Result<()> {
+    let root = dir.clone().unwrap_or(std::env::current_dir()?);
+    let path = root.to_string_lossy().to_string();
+    match command {
+        TemplatesCommand::List => {
+            let listing = list_templates(Some(&root))?;
+            if json {
+                output_json("templates.list", &path, serde_json::to_value(&listing)?);
+            } else if !quiet {
+                println!("{}", format_template_list(&listing));
+            }
+        }
+        TemplatesCommand::Show { slug } => {
+            let template = show_template(Some(&root), &slug)?;
+            if json {
+                output_json("templates.show", &path, serde_json::to_value(&template)?);
+            } else if !quiet {
+                println!("{}", format_template(&template));
+            }
+        }
+        TemplatesCommand::Validate { file } => {
+            let lint = lint_template(Some(&root), &file)?;
+            if json {
+                output_json("templates.validate", &path, serde_json::to_value(&lint)?);
+            } else if !quiet {
+                println!("{}", format_template_lint(&lint));
+            }
+            if !lint.valid {
+                std::process::exit(2);
+            }
+        }
+        TemplatesCommand::Upgrade {
+            to,
+            map,
+            allow_downgrade,
+        } => {
+            let workspace = resolve_workspace(dir, disclosure)?;
+            let mut mappings = Vec::new();
+            for entry in map {
+                let (old, new) = entry.split_once('=').ok_or_else(|| {
+                    ValidationError::new(format!("Invalid --map {}, expected old=new", entry))
+                })?;
+                mappings.push((old.to_string(), new.to_string()));
+            }
+            let report = upgrade_template(&workspace, to.as_deref(), mappings, allow_downgrade)?;
+            if json {
+                output_json("templates.upgrade", &path, serde_json::to_value(&report)?);
+            } else if !quiet {
+                println!("{}", format_template_upgrade(&report));
+            }
+            if let Some(split) = report
+                .split
+                .as_ref()
+                .filter(|split| !split.within_tolerance)
+            {
+                eprintln!("Warning: {}", split.message());
+            }
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
@@ -184,22 +755,64 @@ async fn main() {
         match cli.command {
             Commands::Init {
                 template,
+                template_file,
                 title,
                 author,
                 out,
                 link,
+                force,
+                from,
             } => {
-                let workspace = init_workspace(out.clone(), &template, &title, author, link)?;
+                let config_root = cli.path.clone().unwrap_or(std::env::current_dir()?);
+                let config = Config::load(Some(&config_root))?;
+                // A --template-file or --from picks the template on its own.
+                let template = template.or_else(|| {
+                    (template_file.is_none() && from.is_none())
+                        .then(|| config.get("init.template"))
+                        .flatten()
+                });
+                let report = init_workspace(
+                    out.clone(),
+                    template.as_deref(),
+                    template_file.as_deref(),
+                    title.as_deref(),
+                    author.or_else(|| config.get("init.author")),
+                    link,
+                    force,
+                    from.as_deref(),
+                )?;
+                let workspace = report.workspace;
+                if let Some(name) = &cli.disclosure {
+                    let registry_dir = cli.path.clone().unwrap_or(std::env::current_dir()?);
+                    workspace::WorkspaceRegistry::register(
+                        &registry_dir,
+                        name,
+                        workspace.root_path(),
+                    )?;
+                }
                 if cli.json {
+                    let manifest =
+                        manifest::DisclosureManifest::read_from(&workspace.disclosure_path())?;
                     output_json(
                         "init",
                         workspace.root_path().to_string_lossy().as_ref(),
                         json!({
-                            "template": template,
-                            "disclosure": workspace.disclosure_path().to_string_lossy()
+                            "template": manifest.template.slug,
+                            "disclosure": workspace.disclosure_path().to_string_lossy(),
+                            "backup": report.backup,
+                            "imported_from": report.imported_from,
                         }),
                     );
                 } else if !cli.quiet {
+                    if let Some(backup) = &report.backup {
+                        println!("Previous manifest moved to {}", backup.display());
+                    }
+                    if let Some(source) = &report.imported_from {
+                        println!(
+                            "Imported project, tools and assistance from {}",
+                            source.display()
+                        );
+                    }
                     println!("Created disclosure at {}", workspace.root_path().display());
                 }
                 Ok(())
@@ -213,8 +826,9 @@ async fn main() {
                 copy_into,
                 git,
                 commit,
+                slot,
             } => {
-                let workspace = resolve_workspace(cli.path)?;
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
                 if proof.is_empty() && git.is_none() {
                     return Err(anyhow!("Provide --proof or --git/--commit"));
                 }
@@ -233,7 +847,7 @@ async fn main() {
                     _ => None,
                 };
                 let hashes = attach_proof(
-                    &workspace, proof, label, note, created, copy_into, git_tuple,
+                    &workspace, proof, label, note, created, copy_into, git_tuple, slot,
                 )?;
                 if cli.json {
                     output_json(
@@ -254,35 +868,161 @@ async fn main() {
                 global_ai,
                 stage,
                 allow_unknown_stages,
+                split_mode,
+                split_tolerance,
+                scope,
+                scope_ai,
+                scope_human,
+                scope_grade,
+                scope_note,
+                remove_scope,
+                from_git,
+                apply,
             } => {
-                let workspace = resolve_workspace(cli.path)?;
-                let parsed_stages = stage
-                    .into_iter()
-                    .filter_map(|entry| {
-                        entry
-                            .split_once('=')
-                            .map(|(key, grade)| (key.to_string(), grade.to_string()))
-                    })
-                    .collect();
-                let hashes = update_meter(
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                // Every flag is checked before the manifest is touched.
+                if split_mode == SplitMode::Derive
+                    && (global_human.is_some() || global_ai.is_some())
+                {
+                    return Err(ValidationError::new(
+                        "--split-mode derive computes the global split; drop --global-human/--global-ai",
+                    )
+                    .into());
+                }
+                let mut parsed_stages = Vec::new();
+                for entry in stage {
+                    let (key, grade) = entry.split_once('=').ok_or_else(|| {
+                        ValidationError::new(format!(
+                            "Invalid --stage {}, expected key=grade",
+                            entry
+                        ))
+                    })?;
+                    parsed_stages.push((key.to_string(), grade.to_string()));
+                }
+                let scope_change = match (scope, remove_scope) {
+                    (Some(scope), _) => Some(ScopeChange::Set(ScopeUpdate {
+                        scope: parse_scope(&scope)?,
+                        ai_percent: scope_ai,
+                        human_percent: scope_human,
+                        grade: scope_grade,
+                        notes: scope_note,
+                    })),
+                    (None, Some(scope)) => Some(ScopeChange::Remove(parse_scope(&scope)?)),
+                    (None, None) => None,
+                };
+                let scope_only = scope_change.is_some()
+                    && global_human.is_none()
+                    && global_ai.is_none()
+                    && parsed_stages.is_empty()
+                    && split_mode != SplitMode::Derive;
+                if scope_only {
+                    let report = meter_workspace(&workspace, scope_change, None, None)?;
+                    if cli.json {
+                        output_json(
+                            "meter",
+                            workspace.root_path().to_string_lossy().as_ref(),
+                            json!({ "bundle_root": report.hashes.bundle_root_sha256 }),
+                        );
+                    } else if !cli.quiet {
+                        println!(
+                            "Updated scoped assistance. Bundle root: {}",
+                            report.hashes.bundle_root_sha256
+                        );
+                    }
+                    return Ok(());
+                }
+                let analysis = match from_git {
+                    Some(repo) => Some(analyze_git_history(&workspace, &repo)?),
+                    None => None,
+                };
+                let (mut global_human, mut global_ai) = (global_human, global_ai);
+                if let Some(analysis) = &analysis {
+                    // Explicit flags win over the suggestion from history.
+                    if global_human.is_none()
+                        && global_ai.is_none()
+                        && split_mode != SplitMode::Derive
+                    {
+                        global_ai = Some(analysis.ai_percent);
+                        global_human = Some(100 - analysis.ai_percent);
+                    }
+                    for suggested in &analysis.stages {
+                        if !parsed_stages.iter().any(|(key, _)| *key == suggested.key) {
+                            parsed_stages.push((suggested.key.clone(), suggested.grade.clone()));
+                        }
+                    }
+                }
+                // History only proposes a split until it is accepted.
+                if let Some(analysis) = analysis.as_ref().filter(|_| !apply && !cli.yes) {
+                    let proposal = format_git_proposal(analysis, global_ai, &parsed_stages);
+                    let interactive = !cli.json && !cli.quiet && std::io::stdin().is_terminal();
+                    if !interactive {
+                        if cli.json {
+                            let stages: Vec<_> = parsed_stages
+                                .iter()
+                                .map(|(key, grade)| json!({ "key": key, "grade": grade }))
+                                .collect();
+                            output_json(
+                                "meter",
+                                workspace.root_path().to_string_lossy().as_ref(),
+                                json!({
+                                    "applied": false,
+                                    "git": analysis,
+                                    "proposed": { "ai_percent": global_ai, "stages": stages },
+                                }),
+                            );
+                        } else if !cli.quiet {
+                            println!("{}", proposal);
+                            println!(
+                                "\nNothing written. Re-run with --apply to accept this proposal"
+                            );
+                        }
+                        return Ok(());
+                    }
+                    println!("{}", proposal);
+                    if !confirm("Apply this proposal to disclosure.json?")? {
+                        return Ok(());
+                    }
+                }
+                let report = meter_workspace(
                     &workspace,
-                    global_human,
-                    global_ai,
-                    parsed_stages,
-                    allow_unknown_stages,
+                    scope_change,
+                    Some(MeterChanges {
+                        global_human,
+                        global_ai,
+                        stages: parsed_stages,
+                        allow_unknown: allow_unknown_stages,
+                        split_mode,
+                        tolerance: split_tolerance,
+                    }),
+                    analysis.as_ref(),
                 )?;
+                let warning = report
+                    .split
+                    .as_ref()
+                    .filter(|check| !check.within_tolerance)
+                    .map(|check| check.message());
                 if cli.json {
                     output_json(
                         "meter",
                         workspace.root_path().to_string_lossy().as_ref(),
-                        json!({ "bundle_root": hashes.bundle_root_sha256 }),
+                        json!({
+                            "bundle_root": report.hashes.bundle_root_sha256,
+                            "split": report.split,
+                            "git": analysis,
+                        }),
                     );
                 } else if !cli.quiet {
+                    if let Some(analysis) = &analysis {
+                        println!("{}", analysis.summary());
+                    }
                     println!(
                         "Updated assistance meter. Bundle root: {}",
-                        hashes.bundle_root_sha256
+                        report.hashes.bundle_root_sha256
                     );
                 }
+                if let Some(warning) = warning {
+                    eprintln!("Warning: {}", warning);
+                }
                 Ok(())
             }
             Commands::Stamp {
@@ -296,7 +1036,13 @@ async fn main() {
                 if !ots {
                     return Err(anyhow!("--ots flag is required to stamp"));
                 }
-                let workspace = resolve_workspace(cli.path)?;
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let config = Config::load(Some(workspace.root_path()))?;
+                let calendars = calendars.or_else(|| config.get("stamp.calendars"));
+                let timeout = match timeout {
+                    Some(timeout) => Some(timeout),
+                    None => config.parsed("stamp.timeout")?,
+                };
                 stamp_workspace(&workspace, digest, calendars, Some(out), upgrade, timeout)?;
                 if cli.json {
                     output_json(
@@ -310,7 +1056,7 @@ async fn main() {
                 Ok(())
             }
             Commands::Upgrade { receipt } => {
-                let workspace = resolve_workspace(cli.path)?;
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
                 let changed = upgrade_receipt(&workspace, receipt)?;
                 if cli.json {
                     output_json(
@@ -326,7 +1072,7 @@ async fn main() {
             Commands::Verify {
                 receipt, timeout, ..
             } => {
-                let workspace = resolve_workspace(cli.path)?;
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
                 let ok = verify_receipt(&workspace, receipt, timeout)?;
                 if cli.json {
                     output_json(
@@ -343,7 +1089,7 @@ async fn main() {
                 Ok(())
             }
             Commands::Info { receipt } => {
-                let workspace = resolve_workspace(cli.path)?;
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
                 let info = info_receipt(&workspace, receipt)?;
                 if cli.json {
                     output_json(
@@ -361,44 +1107,450 @@ async fn main() {
                 include_proof,
                 include_receipts,
                 format,
+                path_policy,
+                allow_sensitive,
             } => {
-                let workspace = resolve_workspace(cli.path)?;
-                let include_receipts = matches!(include_receipts.as_str(), "yes" | "true");
-                export_bundle(
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let config = Config::load(Some(workspace.root_path()))?;
+                let bundle = bundle
+                    .or_else(|| config.get("export.bundle").map(PathBuf::from))
+                    .unwrap_or_else(|| PathBuf::from("disclosure-bundle.zip"));
+                let include_proof = match include_proof {
+                    Some(include_proof) => include_proof,
+                    None => config
+                        .parsed("export.include_proof")?
+                        .unwrap_or(IncludeProof::Hashes),
+                };
+                let format = match format {
+                    Some(format) => format,
+                    None => config.parsed("export.format")?.unwrap_or(ExportFormat::Zip),
+                };
+                let include_receipts = include_receipts
+                    .or_else(|| config.get("export.include_receipts"))
+                    .is_none_or(|value| matches!(value.as_str(), "yes" | "true"));
+                let findings = scan_export(&workspace, include_proof, path_policy)?;
+                let interactive = !cli.json && !cli.quiet && std::io::stdin().is_terminal();
+                let Some(allow_sensitive) =
+                    review_findings(&findings, allow_sensitive, interactive, "Export anyway?")?
+                else {
+                    return Ok(());
+                };
+                ensure_allowed(&findings, allow_sensitive)?;
+                let completeness = export_bundle(
                     &workspace,
                     bundle.clone(),
                     include_proof,
                     include_receipts,
                     format,
+                    path_policy,
                 )?;
                 if cli.json {
                     output_json(
                         "export",
                         workspace.root_path().to_string_lossy().as_ref(),
-                        json!({ "bundle": bundle.to_string_lossy() }),
+                        json!({
+                            "bundle": bundle.to_string_lossy(),
+                            "completeness": completeness,
+                            "findings": findings
+                        }),
                     );
                 } else if !cli.quiet {
                     println!("Exported bundle to {}", bundle.display());
+                    if let Some(completeness) = completeness.filter(|c| !c.slots.is_empty()) {
+                        println!(
+                            "Recommended proof: {}% complete\n{}",
+                            completeness.score_percent,
+                            format_checklist(&completeness).trim_end()
+                        );
+                    }
                 }
                 Ok(())
             }
-            Commands::Publish {
+            Commands::Publish(args) => {
+                run_publish(args, cli.path, cli.disclosure.as_deref(), cli.json, cli.quiet, cli.yes)
+                    .await
+            }
+            Commands::Fetch {
+                source,
                 endpoint,
+                out,
+                timeout,
+            } => {
+                let start = std::env::current_dir()?;
+                let (origin, slug) = parse_fetch_source(&source)?;
+                let config = Config::load(None)?;
+                let endpoint = match endpoint.or(origin) {
+                    Some(endpoint) => endpoint,
+                    None => config
+                        .get("publish.endpoint")
+                        .ok_or_else(|| {
+                            ValidationError::new(
+                                "Provide --endpoint, a publication URL or set publish.endpoint",
+                            )
+                        })?,
+                };
+                let out = out.unwrap_or_else(|| start.join(&slug));
+                let report = fetch_workspace(
+                    &endpoint,
+                    &slug,
+                    &out,
+                    timeout,
+                    http_options(&config, timeout, None)?,
+                )
+                .await?;
+                let ok = report.mismatches().is_empty();
+                if cli.json {
+                    output_json(
+                        "fetch",
+                        out.to_string_lossy().as_ref(),
+                        json!({ "report": report, "mismatches": report.mismatches(), "ok": ok }),
+                    );
+                } else if !cli.quiet {
+                    println!("{}", format_fetch_report(&report));
+                }
+                if !ok {
+                    std::process::exit(3);
+                }
+                Ok(())
+            }
+            Commands::Unpublish {
+                target,
                 token,
-                include_receipts,
+                timeout,
+                retries,
             } => {
-                let workspace = resolve_workspace(cli.path)?;
-                let include_receipts = matches!(include_receipts.as_str(), "yes" | "true");
-                let (slug, url) =
-                    publish_workspace(&workspace, &endpoint, token, include_receipts).await?;
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let config = Config::load(Some(workspace.root_path()))?;
+                let target =
+                    publish_target(target, &config, http_options(&config, timeout, retries)?)?;
+                let interactive = !cli.json && !cli.quiet && std::io::stdin().is_terminal();
+                if interactive
+                    && !cli.yes
+                    && !confirm("Delete the public page for this disclosure?")?
+                {
+                    return Ok(());
+                }
+                let slug = unpublish_workspace(&workspace, &*target, token).await?;
                 if cli.json {
                     output_json(
-                        "publish",
+                        "unpublish",
                         workspace.root_path().to_string_lossy().as_ref(),
-                        json!({ "slug": slug, "url": url }),
+                        json!({ "slug": slug }),
+                    );
+                } else if !cli.quiet {
+                    println!("Unpublished: {}", slug);
+                }
+                Ok(())
+            }
+            Commands::Login { endpoint } => {
+                let start = cli.path.clone().unwrap_or(std::env::current_dir()?);
+                let workspace = resolve_workspace(cli.path.clone(), cli.disclosure.as_deref()).ok();
+                let config = Config::load(workspace.as_ref().map(Workspace::root_path))?;
+                let endpoint = endpoint
+                    .or_else(|| config.get("publish.endpoint"))
+                    .ok_or_else(|| {
+                        ValidationError::new("Provide --endpoint or set publish.endpoint")
+                    })?;
+                credentials::endpoint_key(&endpoint)?;
+                let token = if std::io::stdin().is_terminal() {
+                    rpassword::prompt_password(format!("Token for {}: ", endpoint))?
+                } else {
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    line
+                };
+                let file = credentials::save_token(&endpoint, &token)?;
+                if cli.json {
+                    output_json(
+                        "login",
+                        &start.to_string_lossy(),
+                        json!({ "endpoint": endpoint, "credentials": file }),
                     );
                 } else if !cli.quiet {
-                    println!("Published: {}", url);
+                    println!("Saved token for {} in {}", endpoint, file.display());
+                }
+                Ok(())
+            }
+            Commands::Logout { endpoint, all } => {
+                let start = cli.path.clone().unwrap_or(std::env::current_dir()?);
+                let endpoint = if all {
+                    None
+                } else {
+                    let workspace =
+                        resolve_workspace(cli.path.clone(), cli.disclosure.as_deref()).ok();
+                    let config = Config::load(workspace.as_ref().map(Workspace::root_path))?;
+                    Some(
+                        endpoint
+                            .or_else(|| config.get("publish.endpoint"))
+                            .ok_or_else(|| {
+                                ValidationError::new(
+                                    "Provide --endpoint, --all or set publish.endpoint",
+                                )
+                            })?,
+                    )
+                };
+                let removed = credentials::remove_tokens(endpoint.as_deref())?;
+                if cli.json {
+                    output_json(
+                        "logout",
+                        &start.to_string_lossy(),
+                        json!({ "removed": removed }),
+                    );
+                } else if !cli.quiet {
+                    if removed.is_empty() {
+                        println!("No stored token to remove");
+                    } else {
+                        println!("Removed token for {}", removed.join(", "));
+                    }
+                }
+                Ok(())
+            }
+            Commands::Status => {
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let status = workspace_status(&workspace)?;
+                if cli.json {
+                    output_json(
+                        "status",
+                        workspace.root_path().to_string_lossy().as_ref(),
+                        serde_json::to_value(&status)?,
+                    );
+                } else if !cli.quiet {
+                    println!("{}", format_status(&status));
+                }
+                Ok(())
+            }
+            Commands::Migrate => {
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let report = migrate_workspace(&workspace)?;
+                if cli.json {
+                    output_json(
+                        "migrate",
+                        workspace.root_path().to_string_lossy().as_ref(),
+                        serde_json::to_value(&report)?,
+                    );
+                } else if !cli.quiet {
+                    if report.applied.is_empty() {
+                        println!("Manifest is already at version {}", report.to_version);
+                    } else {
+                        println!(
+                            "Migrated manifest from {} to {}",
+                            report.from_version, report.to_version
+                        );
+                        for step in &report.applied {
+                            println!("  {}", step);
+                        }
+                        if report.manifest_hash_changed {
+                            println!("Manifest hash changed; hashes.json was rewritten.");
+                        }
+                        if report.receipt_invalidated {
+                            println!(
+                                "Warning: existing receipt no longer matches. Run `disclose stamp` again."
+                            );
+                        }
+                        if report.publication_stale {
+                            println!("Warning: the published disclosure is now out of date.");
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Commands::Templates { command } => {
+                run_templates(command, cli.path, cli.disclosure.as_deref(), cli.json, cli.quiet)
+            }
+            Commands::Project { command } => {
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let path = workspace.root_path().to_string_lossy().to_string();
+                match command {
+                    ProjectCommand::Set {
+                        title,
+                        author,
+                        add_links,
+                        remove_links,
+                        audience,
+                        assistance_notes,
+                        path_policy,
+                    } => {
+                        let report = set_project(
+                            &workspace,
+                            ProjectUpdate {
+                                title,
+                                author,
+                                add_links,
+                                remove_links,
+                                audience,
+                                assistance_notes,
+                                path_policy,
+                            },
+                        )?;
+                        if cli.json {
+                            output_json("project.set", &path, json!(report));
+                        } else if !cli.quiet {
+                            println!("{}", format_project_report(&report));
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Commands::Config { command } => {
+                let start = cli.path.clone().unwrap_or(std::env::current_dir()?);
+                let workspace = resolve_workspace(cli.path.clone(), cli.disclosure.as_deref()).ok();
+                let target = |user: bool| -> Result<PathBuf> {
+                    if user {
+                        return config::user_config_path()
+                            .ok_or_else(|| anyhow!("No user config directory on this system"));
+                    }
+                    workspace
+                        .as_ref()
+                        .map(|workspace| config::workspace_config_path(workspace.root_path()))
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "disclosure.json not found; use --user to change the user config"
+                            )
+                        })
+                };
+                let path = start.to_string_lossy().to_string();
+                match command {
+                    ConfigCommand::List => {
+                        let config = Config::load(workspace.as_ref().map(Workspace::root_path))?;
+                        if cli.json {
+                            output_json(
+                                "config.list",
+                                &path,
+                                json!({ "values": config.entries() }),
+                            );
+                        } else if !cli.quiet {
+                            println!("{}", format_config_list(&config));
+                        }
+                    }
+                    ConfigCommand::Get { key } => {
+                        let config = Config::load(workspace.as_ref().map(Workspace::root_path))?;
+                        let entry = get_config(&config, &key)?;
+                        if cli.json {
+                            output_json("config.get", &path, json!({ "key": key, "entry": entry }));
+                        } else if !cli.quiet {
+                            match &entry {
+                                Some(entry) => println!("{}", format_config_value(entry)),
+                                None => println!("{} is not set", key),
+                            }
+                        }
+                        if entry.is_none() {
+                            std::process::exit(1);
+                        }
+                    }
+                    ConfigCommand::Set { key, value, user } => {
+                        let file = target(user)?;
+                        set_config(&file, &key, &value)?;
+                        if cli.json {
+                            output_json(
+                                "config.set",
+                                &path,
+                                json!({ "key": key, "value": value, "file": file }),
+                            );
+                        } else if !cli.quiet {
+                            println!("Set {} in {}", key, file.display());
+                        }
+                    }
+                    ConfigCommand::Unset { key, user } => {
+                        let file = target(user)?;
+                        unset_config(&file, &key)?;
+                        if cli.json {
+                            output_json("config.unset", &path, json!({ "key": key, "file": file }));
+                        } else if !cli.quiet {
+                            println!("Removed {} from {}", key, file.display());
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Commands::Tools { command } => {
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let path = workspace.root_path().to_string_lossy().to_string();
+                match command {
+                    ToolsCommand::List => {
+                        let tools = list_tools(&workspace)?;
+                        if cli.json {
+                            output_json("tools.list", &path, json!({ "tools": tools }));
+                        } else if !cli.quiet {
+                            println!("{}", format_tool_list(&tools));
+                        }
+                    }
+                    ToolsCommand::Add {
+                        name,
+                        provider,
+                        model,
+                        version,
+                        notes,
+                        stages,
+                    } => {
+                        let hashes = add_tool(
+                            &workspace,
+                            manifest::AiTool {
+                                name: name.clone(),
+                                provider,
+                                model,
+                                version,
+                                notes,
+                                stages: (!stages.is_empty()).then_some(stages),
+                                extra: Default::default(),
+                            },
+                        )?;
+                        if cli.json {
+                            output_json(
+                                "tools.add",
+                                &path,
+                                json!({ "name": name, "bundle_root": hashes.bundle_root_sha256 }),
+                            );
+                        } else if !cli.quiet {
+                            println!(
+                                "Added AI tool {}. Bundle root: {}",
+                                name, hashes.bundle_root_sha256
+                            );
+                        }
+                    }
+                    ToolsCommand::Remove { name } => {
+                        let hashes = remove_tool(&workspace, &name)?;
+                        if cli.json {
+                            output_json(
+                                "tools.remove",
+                                &path,
+                                json!({ "name": name, "bundle_root": hashes.bundle_root_sha256 }),
+                            );
+                        } else if !cli.quiet {
+                            println!(
+                                "Removed AI tool {}. Bundle root: {}",
+                                name, hashes.bundle_root_sha256
+                            );
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Commands::ImportUsage {
+                files,
+                format,
+                apply,
+            } => {
+                let workspace = resolve_workspace(cli.path, cli.disclosure.as_deref())?;
+                let path = workspace.root_path().to_string_lossy().to_string();
+                let interactive = !cli.json && !cli.quiet && std::io::stdin().is_terminal();
+                let mut report = import_usage(&workspace, &files, format, apply || cli.yes)?;
+                if !report.applied && interactive {
+                    println!("{}", format_usage_import(&report));
+                    if !confirm("Apply this proposal to disclosure.json?")? {
+                        return Ok(());
+                    }
+                    report = import_usage(&workspace, &files, format, true)?;
+                    println!("Applied usage proposal to disclosure.json");
+                } else if cli.json {
+                    output_json("import-usage", &path, json!(report));
+                } else if !cli.quiet {
+                    println!("{}", format_usage_import(&report));
+                    if !report.applied {
+                        println!("\nNothing written. Re-run with --apply to accept this proposal");
+                    }
+                }
+                if let Some(split) = report.split.as_ref().filter(|split| !split.within_tolerance) {
+                    eprintln!("Warning: {}", split.message());
                 }
                 Ok(())
             }
@@ -412,10 +1564,12 @@ async fn main() {
     .await;
 
     if let Err(err) = result {
-        if !cli.quiet {
+        let code = error_code(&err);
+        if cli.json {
+            println!("{}", error_json(&err, code));
+        } else if !cli.quiet {
             eprintln!("Error: {}", err);
         }
-        let code = error_code(&err);
         std::process::exit(code);
     }
 }
@@ -1,9 +1,18 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Fields this build does not know about, e.g. `x-` extensions written by the
+/// web app or other tools. They are kept verbatim so rewriting a manifest
+/// never drops them, and they take part in the manifest hash.
+pub type Extensions = BTreeMap<String, Value>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateRef {
     pub slug: String,
     pub version: String,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +24,8 @@ pub struct ProjectInfo {
     pub links: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audience: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,23 +36,46 @@ pub struct AiTool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+    /// Keys of the assistance stages the tool was used in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stages: Option<Vec<String>>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssistanceGlobal {
     pub human_percent: i32,
     pub ai_percent: i32,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum AssistanceGrade {
-    None,
-    Light,
-    Moderate,
-    Heavy,
-    Full,
+/// A grade key from the template's grade scale, e.g. `light`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssistanceGrade(String);
+
+impl AssistanceGrade {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The range of the grade a stage was recorded with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PercentRange {
+    pub min: i32,
+    pub max: i32,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +84,60 @@ pub struct AssistanceStage {
     pub label: String,
     pub grade: AssistanceGrade,
     pub approx_ai_percent: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_percent_range: Option<PercentRange>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
+}
+
+/// What a scoped assistance entry covers: one proof item, the proof paths
+/// matching a glob, or a named component of the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum AssistanceScope {
+    Proof(String),
+    Glob(String),
+    Component(String),
+}
+
+impl AssistanceScope {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AssistanceScope::Proof(_) => "proof",
+            AssistanceScope::Glob(_) => "glob",
+            AssistanceScope::Component(_) => "component",
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match self {
+            AssistanceScope::Proof(value)
+            | AssistanceScope::Glob(value)
+            | AssistanceScope::Component(value) => value,
+        }
+    }
+}
+
+impl std::fmt::Display for AssistanceScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind(), self.value())
+    }
+}
+
+/// Assistance for part of the project, e.g. "tests 80% AI".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopedAssistance {
+    pub scope: AssistanceScope,
+    pub human_percent: i32,
+    pub ai_percent: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<AssistanceGrade>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_percent_range: Option<PercentRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,7 +146,11 @@ pub struct AssistanceInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stages: Option<Vec<AssistanceStage>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub scoped: Option<Vec<ScopedAssistance>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +167,8 @@ pub struct GitProof {
     pub repo: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commit: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,13 +189,118 @@ pub struct ProofItem {
     pub notes: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git: Option<GitProof>,
+    /// Key of the template's recommended proof this item was attached for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofSlot {
+    pub key: String,
+    pub label: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    pub proof_ids: Vec<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
+}
+
+/// How much of the template's `recommended_proof` is covered. The score only
+/// counts required slots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofCompleteness {
+    pub score_percent: u8,
+    pub slots: Vec<ProofSlot>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
+}
+
+/// Prefix of a `proof.items[].path` redacted with [`PathPolicy::Hash`].
+pub const HASHED_PATH_PREFIX: &str = "sha256:";
+
+/// How `proof.items[].path` appears in exported and published manifests.
+/// The workspace keeps the real relative paths; the policy is recorded in
+/// `proof.path_policy` so verifiers know how to resolve what they get.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathPolicy {
+    /// The path relative to the workspace, as attached.
+    #[default]
+    Relative,
+    /// Only the file name.
+    Basename,
+    /// `sha256:` of the relative path with `/` separators. This hides the
+    /// path from readers, but anyone who can guess it can confirm the guess.
+    Hash,
+}
+
+impl PathPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathPolicy::Relative => "relative",
+            PathPolicy::Basename => "basename",
+            PathPolicy::Hash => "hash",
+        }
+    }
+
+    /// Redacts a workspace path. Applying a policy to a path it already
+    /// redacted returns it unchanged.
+    pub fn apply(&self, path: &str) -> String {
+        match self {
+            PathPolicy::Relative => path.to_string(),
+            PathPolicy::Basename => path.rsplit(['/', '\\']).next().unwrap_or(path).to_string(),
+            PathPolicy::Hash if path.starts_with(HASHED_PATH_PREFIX) => path.to_string(),
+            PathPolicy::Hash => format!(
+                "{}{}",
+                HASHED_PATH_PREFIX,
+                crate::hashing::sha256_hex_bytes(path.replace('\\', "/").as_bytes())
+            ),
+        }
+    }
+
+    /// File name of a proof copy in an exported bundle's `proof/` folder:
+    /// the base name, or the path hash without its prefix.
+    pub fn bundle_name(&self, path: &str) -> String {
+        match self {
+            PathPolicy::Hash => self.apply(path)[HASHED_PATH_PREFIX.len()..].to_string(),
+            _ => PathPolicy::Basename.apply(path),
+        }
+    }
+}
+
+impl std::fmt::Display for PathPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PathPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Self> {
+        match input {
+            "relative" => Ok(PathPolicy::Relative),
+            "basename" => Ok(PathPolicy::Basename),
+            "hash" => Ok(PathPolicy::Hash),
+            _ => Err(anyhow::anyhow!("Invalid path policy")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofInfo {
     pub items: Vec<ProofItem>,
+    /// Absent means [`PathPolicy::Relative`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_policy: Option<PathPolicy>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bundle_root_sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<ProofCompleteness>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,12 +313,16 @@ pub struct OpenTimestampsInfo {
     pub receipt_sha256: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub receipt_filename: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimestampInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub opentimestamps: Option<OpenTimestampsInfo>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,6 +333,16 @@ pub struct PublicationInfo {
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub published_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>,
+    /// Publish target holding the publication: `api`, `static` or `s3`.
+    /// Absent for publications made through the web API.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,17 +360,141 @@ pub struct DisclosureManifest {
     pub timestamps: Option<TimestampInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub publication: Option<PublicationInfo>,
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: Extensions,
 }
 
 impl DisclosureManifest {
+    /// Reads `disclosure.json`, upgrading older manifest versions in memory.
     pub fn read_from(path: &std::path::Path) -> anyhow::Result<Self> {
+        let value = Self::read_value(path)?;
+        Self::from_value(value)
+    }
+
+    /// Reads `disclosure.json` exactly as stored, without migrating it.
+    pub fn read_value(path: &std::path::Path) -> anyhow::Result<serde_json::Value> {
         let data = std::fs::read_to_string(path)?;
         Ok(serde_json::from_str(&data)?)
     }
 
+    pub fn from_value(mut value: serde_json::Value) -> anyhow::Result<Self> {
+        crate::migrations::migrate_value(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
     pub fn write_to(&self, path: &std::path::Path) -> anyhow::Result<()> {
         let json = serde_json::to_string_pretty(self)?;
         std::fs::write(path, json)?;
         Ok(())
     }
+
+    pub fn path_policy(&self) -> PathPolicy {
+        self.proof.path_policy.unwrap_or_default()
+    }
+
+    /// Rewrites proof paths under `policy` and records it. A manifest that
+    /// never set a policy stays without one when `policy` is the default, so
+    /// its hash does not change.
+    pub fn redact_paths(&mut self, policy: PathPolicy) {
+        for item in &mut self.proof.items {
+            if let Some(path) = &item.path {
+                item.path = Some(policy.apply(path));
+            }
+        }
+        if self.proof.path_policy.is_some() || policy != PathPolicy::Relative {
+            self.proof.path_policy = Some(policy);
+        }
+    }
+
+    /// Whether an OpenTimestamps receipt has been recorded for this manifest.
+    pub fn has_receipt(&self) -> bool {
+        self.timestamps
+            .as_ref()
+            .and_then(|timestamps| timestamps.opentimestamps.as_ref())
+            .and_then(|ots| ots.receipt_sha256.as_ref())
+            .is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_fields_survive_round_trip() {
+        let input = json!({
+            "version": "1.0.0",
+            "id": "dsc_test",
+            "created_at": "2026-01-13T12:00:00Z",
+            "template": { "slug": "article", "version": "1.0.0" },
+            "project": { "title": "Post", "x-web": { "theme": "paper" } },
+            "assistance": {
+                "global": { "human_percent": 80, "ai_percent": 20 },
+                "stages": [
+                    { "key": "drafting", "label": "Drafting", "grade": "light", "approx_ai_percent": 10, "confidence": "high" }
+                ]
+            },
+            "proof": { "items": [] },
+            "x-acme": { "ticket": "ACME-1" }
+        });
+        let manifest: DisclosureManifest = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(manifest.extra["x-acme"], json!({ "ticket": "ACME-1" }));
+        assert_eq!(serde_json::to_value(&manifest).unwrap(), input);
+
+        let without: DisclosureManifest = {
+            let mut value = input.clone();
+            value.as_object_mut().unwrap().remove("x-acme");
+            serde_json::from_value(value).unwrap()
+        };
+        assert_ne!(
+            crate::hashing::manifest_hash(&manifest).unwrap(),
+            crate::hashing::manifest_hash(&without).unwrap()
+        );
+    }
+
+    #[test]
+    fn redacted_paths_keep_the_manifest_hash() {
+        let input = json!({
+            "version": "1.0.0",
+            "id": "dsc_test",
+            "created_at": "2026-01-13T12:00:00Z",
+            "template": { "slug": "article", "version": "1.0.0" },
+            "project": { "title": "Post" },
+            "assistance": {
+                "global": { "human_percent": 80, "ai_percent": 20 },
+                "stages": []
+            },
+            "proof": {
+                "items": [{
+                    "id": "p_1",
+                    "label": "Draft",
+                    "kind": "file",
+                    "path": "../../home/alice/secret/draft.md",
+                    "sha256": "ab"
+                }]
+            }
+        });
+        let relative: DisclosureManifest = serde_json::from_value(input).unwrap();
+        let mut local = relative.clone();
+        local.proof.path_policy = Some(PathPolicy::Basename);
+
+        let mut exported = local.clone();
+        exported.redact_paths(PathPolicy::Basename);
+        assert_eq!(exported.proof.items[0].path.as_deref(), Some("draft.md"));
+        let hash = |manifest: &DisclosureManifest| crate::hashing::manifest_hash(manifest).unwrap();
+        assert_eq!(hash(&exported), hash(&local));
+        assert_ne!(hash(&local), hash(&relative));
+
+        let mut unchanged = relative.clone();
+        unchanged.redact_paths(PathPolicy::Relative);
+        assert!(unchanged.proof.path_policy.is_none());
+        assert_eq!(hash(&unchanged), hash(&relative));
+
+        let hashed = PathPolicy::Hash.apply("proof/draft.md");
+        assert!(hashed.starts_with(HASHED_PATH_PREFIX));
+        assert_eq!(PathPolicy::Hash.apply(&hashed), hashed);
+        assert_eq!(PathPolicy::Hash.apply(r"proof\draft.md"), hashed);
+        assert_eq!(PathPolicy::Hash.bundle_name("proof/draft.md"), hashed[7..]);
+    }
 }
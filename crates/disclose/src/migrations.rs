@@ -0,0 +1,198 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::errors::ValidationError;
+use crate::templates::default_grade_scale;
+use crate::version::parse_version;
+
+/// Manifest version written by this build of the CLI.
+pub const CURRENT_MANIFEST_VERSION: &str = "1.1.0";
+
+/// A single upgrade step from one manifest version to the next.
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub description: &'static str,
+    pub apply: fn(&mut Value) -> Result<()>,
+}
+
+/// Registered upgrade steps, oldest first. Each step must start at the
+/// version the previous one ends at.
+static MIGRATIONS: &[Migration] = &[Migration {
+    from: "1.0.0",
+    to: "1.1.0",
+    description: "record ai_percent_range for stage grades",
+    apply: add_stage_ranges,
+}];
+
+/// 1.0.0 manifests could only use the default grade scale, so each stage's
+/// range is taken from it.
+fn add_stage_ranges(value: &mut Value) -> Result<()> {
+    let scale = default_grade_scale();
+    let stages = value
+        .pointer_mut("/assistance/stages")
+        .and_then(Value::as_array_mut);
+    for stage in stages.into_iter().flatten() {
+        let Some(stage) = stage.as_object_mut() else {
+            continue;
+        };
+        if stage.contains_key("ai_percent_range") {
+            continue;
+        }
+        let grade = stage.get("grade").and_then(Value::as_str);
+        if let Some(level) = scale.iter().find(|level| Some(level.key.as_str()) == grade) {
+            stage.insert(
+                "ai_percent_range".to_string(),
+                json!({ "min": level.min_percent, "max": level.max_percent }),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationOutcome {
+    pub from_version: String,
+    pub to_version: String,
+    pub applied: Vec<String>,
+}
+
+impl MigrationOutcome {
+    pub fn changed(&self) -> bool {
+        !self.applied.is_empty()
+    }
+}
+
+pub fn detect_version(value: &Value) -> Result<String> {
+    match value.get("version") {
+        Some(Value::String(version)) => Ok(version.clone()),
+        Some(_) => Err(ValidationError::new("Manifest version must be a string").into()),
+        None => Err(ValidationError::new("Manifest is missing a version").into()),
+    }
+}
+
+/// Upgrades a raw manifest in place until it reaches the current version.
+pub fn migrate_value(value: &mut Value) -> Result<MigrationOutcome> {
+    migrate_with(value, MIGRATIONS, CURRENT_MANIFEST_VERSION)
+}
+
+fn migrate_with(value: &mut Value, steps: &[Migration], target: &str) -> Result<MigrationOutcome> {
+    let from_version = detect_version(value)?;
+    let target_parsed = parse_version(target)
+        .ok_or_else(|| ValidationError::new(format!("Invalid target version {}", target)))?;
+    let mut version = from_version.clone();
+    let mut applied = Vec::new();
+
+    loop {
+        let parsed = parse_version(&version).ok_or_else(|| {
+            ValidationError::new(format!("Unrecognized manifest version {}", version))
+        })?;
+        if parsed == target_parsed {
+            break;
+        }
+        if parsed > target_parsed {
+            return Err(ValidationError::new(format!(
+                "Manifest version {} is newer than this CLI supports ({})",
+                version, target
+            ))
+            .into());
+        }
+        let step = steps
+            .iter()
+            .find(|step| parse_version(step.from) == Some(parsed))
+            .ok_or_else(|| {
+                ValidationError::new(format!(
+                    "No migration registered from manifest version {}",
+                    version
+                ))
+            })?;
+        (step.apply)(value)?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("version".to_string(), Value::String(step.to.to_string()));
+        }
+        applied.push(format!(
+            "{} -> {}: {}",
+            step.from, step.to, step.description
+        ));
+        version = step.to.to_string();
+    }
+
+    Ok(MigrationOutcome {
+        from_version,
+        to_version: version,
+        applied,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_notes(value: &mut Value) -> Result<()> {
+        if let Some(assistance) = value.get_mut("assistance").and_then(Value::as_object_mut) {
+            if let Some(note) = assistance.remove("note") {
+                assistance.insert("notes".to_string(), note);
+            }
+        }
+        Ok(())
+    }
+
+    fn noop(_: &mut Value) -> Result<()> {
+        Ok(())
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration {
+            from: "0.9.0",
+            to: "1.0.0",
+            description: "rename assistance.note",
+            apply: rename_notes,
+        },
+        Migration {
+            from: "1.0.0",
+            to: "1.1.0",
+            description: "no-op",
+            apply: noop,
+        },
+    ];
+
+    #[test]
+    fn migrates_through_registered_steps() {
+        let mut value = json!({ "version": "0.9.0", "assistance": { "note": "hi" } });
+        let outcome = migrate_with(&mut value, STEPS, "1.1.0").unwrap();
+        assert_eq!(outcome.from_version, "0.9.0");
+        assert_eq!(outcome.to_version, "1.1.0");
+        assert_eq!(outcome.applied.len(), 2);
+        assert_eq!(value["version"], "1.1.0");
+        assert_eq!(value["assistance"]["notes"], "hi");
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let mut value = json!({ "version": "1.1.0" });
+        let outcome = migrate_with(&mut value, STEPS, "1.1.0").unwrap();
+        assert!(!outcome.changed());
+    }
+
+    #[test]
+    fn registered_steps_add_stage_ranges() {
+        let mut value = json!({
+            "version": "1.0.0",
+            "assistance": { "stages": [{ "key": "drafting", "grade": "heavy" }] }
+        });
+        let outcome = migrate_value(&mut value).unwrap();
+        assert_eq!(outcome.to_version, CURRENT_MANIFEST_VERSION);
+        assert_eq!(
+            value["assistance"]["stages"][0]["ai_percent_range"],
+            json!({ "min": 46, "max": 75 })
+        );
+    }
+
+    #[test]
+    fn rejects_newer_and_unknown_versions() {
+        let mut newer = json!({ "version": "2.0.0" });
+        assert!(migrate_with(&mut newer, STEPS, "1.1.0").is_err());
+        let mut orphan = json!({ "version": "0.5.0" });
+        assert!(migrate_with(&mut orphan, STEPS, "1.1.0").is_err());
+    }
+}
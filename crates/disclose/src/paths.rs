@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+/// Per-user configuration directory, e.g. `~/.config/disclose` on Linux.
+/// `DISCLOSE_CONFIG_DIR` overrides it.
+pub fn user_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("DISCLOSE_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::config_dir().map(|dir| dir.join("disclose"))
+}
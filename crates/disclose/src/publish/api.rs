@@ -0,0 +1,118 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::Method;
+
+use crate::credentials::resolve_token;
+use crate::errors::ValidationError;
+
+use super::{disclosures_url, send, HttpOptions, PublishResponse, PublishTarget, TargetKind};
+
+/// The Disclose web API: `POST /api/disclosures` creates a publication and
+/// `PUT /api/disclosures/<slug>` replaces it, keeping the old version as a
+/// revision.
+pub struct ApiTarget {
+    endpoint: String,
+    http: HttpOptions,
+}
+
+impl ApiTarget {
+    pub fn new(endpoint: impl Into<String>, http: HttpOptions) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            http,
+        }
+    }
+
+    fn url(&self, slug: Option<&str>) -> String {
+        match slug {
+            Some(slug) => format!("{}/{}", disclosures_url(&self.endpoint), slug),
+            None => disclosures_url(&self.endpoint),
+        }
+    }
+
+    /// The `--token` value, or the one stored by `disclose login`.
+    fn token(&self, token: Option<String>) -> Result<Option<String>> {
+        match token {
+            Some(token) => Ok(Some(token)),
+            None => resolve_token(&self.endpoint),
+        }
+    }
+}
+
+fn headers(token: Option<&str>, json: bool) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if json {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    if let Some(token) = token {
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token))?,
+        );
+    }
+    Ok(headers)
+}
+
+#[async_trait]
+impl PublishTarget for ApiTarget {
+    fn kind(&self) -> TargetKind {
+        TargetKind::Api
+    }
+
+    fn describe(&self, slug: Option<&str>) -> (String, String) {
+        let method = if slug.is_some() { "PUT" } else { "POST" };
+        (method.to_string(), self.url(slug))
+    }
+
+    async fn publish(
+        &self,
+        slug: Option<&str>,
+        body: String,
+        token: Option<String>,
+    ) -> Result<PublishResponse> {
+        let token = self.token(token)?;
+        let method = match slug {
+            Some(slug) => {
+                if token.is_none() {
+                    return Err(ValidationError::new(format!(
+                        "Updating publication {} needs a token. Run `disclose login`, or pass --new to publish a separate copy",
+                        slug
+                    ))
+                    .into());
+                }
+                Method::PUT
+            }
+            None => Method::POST,
+        };
+        let headers = headers(token.as_deref(), true)?;
+        let response = send(
+            self.http,
+            method,
+            &self.url(slug),
+            headers,
+            Some(body.into_bytes()),
+        )
+        .await?;
+        Ok(response.json::<PublishResponse>().await?)
+    }
+
+    async fn unpublish(&self, slug: &str, token: Option<String>) -> Result<()> {
+        let token = self.token(token)?.ok_or_else(|| {
+            ValidationError::new(format!(
+                "Unpublishing {} needs a token. Run `disclose login` first",
+                slug
+            ))
+        })?;
+        let headers = headers(Some(&token), false)?;
+        send(
+            self.http,
+            Method::DELETE,
+            &self.url(Some(slug)),
+            headers,
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+}
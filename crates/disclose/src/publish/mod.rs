@@ -0,0 +1,425 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::errors::ValidationError;
+use crate::hashing::HashesJson;
+use crate::manifest::DisclosureManifest;
+
+mod api;
+mod s3;
+mod site;
+mod static_site;
+
+pub use api::ApiTarget;
+pub use s3::{S3Credentials, S3Settings, S3Target};
+pub use static_site::StaticSiteTarget;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptPayload {
+    pub filename: String,
+    pub bytes_base64: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublishPayload<'a> {
+    pub manifest: &'a DisclosureManifest,
+    pub hashes: &'a HashesJson,
+    pub receipts: Vec<ReceiptPayload>,
+}
+
+impl PublishPayload<'_> {
+    /// The request body exactly as sent.
+    pub fn to_body(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublishResponse {
+    pub slug: String,
+    pub url: String,
+    /// Revision now live; servers without revisions omit it.
+    #[serde(default)]
+    pub revision: Option<u32>,
+}
+
+pub fn disclosures_url(endpoint: &str) -> String {
+    format!("{}/api/disclosures", endpoint.trim_end_matches('/'))
+}
+
+/// Where `publish` sends a disclosure.
+#[async_trait]
+pub trait PublishTarget: Send + Sync {
+    /// The kind, recorded in `publication.target`.
+    fn kind(&self) -> TargetKind;
+
+    /// Method and location shown in plans and `--dry-run`. `slug` is the
+    /// publication being replaced, if any.
+    fn describe(&self, slug: Option<&str>) -> (String, String);
+
+    /// Creates a publication, or replaces the one at `slug`. `body` is the
+    /// JSON of a [`PublishPayload`].
+    async fn publish(
+        &self,
+        slug: Option<&str>,
+        body: String,
+        token: Option<String>,
+    ) -> Result<PublishResponse>;
+
+    async fn unpublish(&self, slug: &str, token: Option<String>) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    /// The Disclose web API at `publish.endpoint`.
+    #[default]
+    Api,
+    /// A directory of HTML and JSON files for any static host.
+    Static,
+    /// The same files in an S3-compatible bucket.
+    S3,
+}
+
+impl TargetKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetKind::Api => "api",
+            TargetKind::Static => "static",
+            TargetKind::S3 => "s3",
+        }
+    }
+}
+
+impl fmt::Display for TargetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TargetKind {
+    type Err = ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "api" => Ok(TargetKind::Api),
+            "static" => Ok(TargetKind::Static),
+            "s3" => Ok(TargetKind::S3),
+            other => Err(ValidationError::new(format!(
+                "Unknown publish target {:?}; expected api, static or s3",
+                other
+            ))),
+        }
+    }
+}
+
+/// A fresh slug for targets that do not assign their own.
+fn new_slug() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Timeout and retry settings for requests to the publish server.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpOptions {
+    /// Limit for a single attempt, from connecting to reading the response.
+    pub timeout: Duration,
+    /// Extra attempts after a 5xx response or a failed connection.
+    pub retries: u32,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retries: 3,
+        }
+    }
+}
+
+/// Delay before the first retry; it doubles for each further one.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest server message kept when the error body is not JSON.
+const MAX_ERROR_TEXT: usize = 300;
+
+/// Why a request to the publish server failed. Serialized into the `--json`
+/// error output.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PublishError {
+    /// The server refused the request (4xx), e.g. a manifest that fails its
+    /// schema validation.
+    Rejected {
+        url: String,
+        status: u16,
+        errors: Vec<String>,
+    },
+    /// Missing or wrong token or keys (401/403).
+    Unauthorized {
+        url: String,
+        status: u16,
+        errors: Vec<String>,
+    },
+    /// 5xx responses on every attempt.
+    Server {
+        url: String,
+        status: u16,
+        errors: Vec<String>,
+        attempts: u32,
+    },
+    Timeout {
+        url: String,
+        attempts: u32,
+    },
+    Unreachable {
+        url: String,
+        attempts: u32,
+        reason: String,
+    },
+}
+
+impl PublishError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PublishError::Rejected { .. } => 2,
+            PublishError::Unauthorized { .. } => 6,
+            PublishError::Server { .. }
+            | PublishError::Timeout { .. }
+            | PublishError::Unreachable { .. } => 4,
+        }
+    }
+
+    async fn from_response(url: &str, response: Response, attempts: u32) -> Self {
+        let status = response.status().as_u16();
+        let errors = server_errors(&response.text().await.unwrap_or_default());
+        let url = url.to_string();
+        match status {
+            401 | 403 => PublishError::Unauthorized {
+                url,
+                status,
+                errors,
+            },
+            500.. => PublishError::Server {
+                url,
+                status,
+                errors,
+                attempts,
+            },
+            _ => PublishError::Rejected {
+                url,
+                status,
+                errors,
+            },
+        }
+    }
+
+    fn from_request(url: &str, err: &reqwest::Error, attempts: u32) -> Self {
+        let url = url.to_string();
+        if err.is_timeout() {
+            return PublishError::Timeout { url, attempts };
+        }
+        // The top-level message only says "error sending request".
+        let mut reason = err.to_string();
+        let mut source = std::error::Error::source(err);
+        while let Some(inner) = source {
+            reason = inner.to_string();
+            source = inner.source();
+        }
+        PublishError::Unreachable {
+            url,
+            attempts,
+            reason,
+        }
+    }
+}
+
+fn write_errors(f: &mut fmt::Formatter<'_>, errors: &[String]) -> fmt::Result {
+    for error in errors {
+        write!(f, "\n  - {}", error)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublishError::Rejected {
+                url,
+                status,
+                errors,
+            } => {
+                write!(f, "{} rejected the request (HTTP {})", url, status)?;
+                write_errors(f, errors)
+            }
+            PublishError::Unauthorized {
+                url,
+                status,
+                errors,
+            } => {
+                write!(
+                    f,
+                    "{} refused the credentials (HTTP {}). Run `disclose login` or pass --token; for the s3 target check AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY",
+                    url, status
+                )?;
+                write_errors(f, errors)
+            }
+            PublishError::Server {
+                url,
+                status,
+                errors,
+                attempts,
+            } => {
+                write!(
+                    f,
+                    "{} failed with HTTP {} after {} attempt(s)",
+                    url, status, attempts
+                )?;
+                write_errors(f, errors)
+            }
+            PublishError::Timeout { url, attempts } => write!(
+                f,
+                "{} did not respond in time after {} attempt(s). Raise --timeout or publish.timeout",
+                url, attempts
+            ),
+            PublishError::Unreachable {
+                url,
+                attempts,
+                reason,
+            } => write!(
+                f,
+                "Could not reach {} after {} attempt(s): {}",
+                url, attempts, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PublishError {}
+
+/// Messages from an error body: the `errors` array the API returns for
+/// validation failures, its single `error`, the `<Message>` of an S3 error,
+/// or the raw text.
+fn server_errors(body: &str) -> Vec<String> {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        let text = body.trim();
+        if let Some((_, rest)) = text.split_once("<Message>") {
+            if let Some((message, _)) = rest.split_once("</Message>") {
+                return vec![message.trim().to_string()];
+            }
+        }
+        return match text.char_indices().nth(MAX_ERROR_TEXT) {
+            Some((end, _)) => vec![format!("{}...", &text[..end])],
+            None if text.is_empty() => Vec::new(),
+            None => vec![text.to_string()],
+        };
+    };
+    if let Some(errors) = value.get("errors").and_then(Value::as_array) {
+        return errors
+            .iter()
+            .map(|error| match error.as_str() {
+                Some(text) => text.to_string(),
+                None => error.to_string(),
+            })
+            .collect();
+    }
+    value
+        .get("error")
+        .and_then(Value::as_str)
+        .map(|error| vec![error.to_string()])
+        .unwrap_or_default()
+}
+
+/// Sends a request, retrying 5xx responses and failed connections with
+/// exponential backoff. Timeouts and 5xx responses are retried only for
+/// idempotent methods: a POST that got either may still have created a
+/// publication.
+async fn send(
+    options: HttpOptions,
+    method: Method,
+    url: &str,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+) -> Result<Response> {
+    let client = Client::builder().timeout(options.timeout).build()?;
+    let idempotent = method != Method::POST;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let mut request = client.request(method.clone(), url).headers(headers.clone());
+        if let Some(body) = &body {
+            request = request.body(body.clone());
+        }
+        let retry = attempts <= options.retries;
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) if response.status().is_server_error() && retry && idempotent => {}
+            Ok(response) => {
+                return Err(PublishError::from_response(url, response, attempts)
+                    .await
+                    .into())
+            }
+            Err(err) if retry && (err.is_connect() || (err.is_timeout() && idempotent)) => {}
+            Err(err) => return Err(PublishError::from_request(url, &err, attempts).into()),
+        }
+        tokio::time::sleep(RETRY_DELAY * 2u32.saturating_pow(attempts - 1)).await;
+    }
+}
+
+/// A publication as served by `GET /api/disclosures/<slug>`. The manifest and
+/// hashes are kept as raw JSON so they can be hashed exactly as served.
+#[derive(Debug, Deserialize)]
+pub struct FetchedDisclosure {
+    pub manifest: Value,
+    pub hashes: Value,
+    #[serde(default)]
+    pub receipts: Vec<ReceiptPayload>,
+}
+
+pub async fn fetch_disclosure(
+    endpoint: &str,
+    slug: &str,
+    options: HttpOptions,
+) -> Result<FetchedDisclosure> {
+    let url = format!("{}/{}", disclosures_url(endpoint), slug);
+    let response = send(options, Method::GET, &url, HeaderMap::new(), None).await?;
+    Ok(response.json::<FetchedDisclosure>().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_bodies_become_messages() {
+        assert_eq!(
+            server_errors(
+                r#"{"ok":false,"errors":["/project: must have required property 'title'"]}"#
+            ),
+            vec!["/project: must have required property 'title'".to_string()]
+        );
+        assert_eq!(
+            server_errors(r#"{"ok":false,"error":"Invalid hashes payload"}"#),
+            vec!["Invalid hashes payload".to_string()]
+        );
+        assert_eq!(
+            server_errors("Bad Gateway\n"),
+            vec!["Bad Gateway".to_string()]
+        );
+        assert_eq!(
+            server_errors(
+                "<?xml version=\"1.0\"?><Error><Code>AccessDenied</Code><Message>Access Denied.</Message></Error>"
+            ),
+            vec!["Access Denied.".to_string()]
+        );
+        assert!(server_errors("").is_empty());
+        assert!(server_errors(&"x".repeat(1000))[0].ends_with("..."));
+    }
+}
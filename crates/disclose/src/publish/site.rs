@@ -0,0 +1,314 @@
+//! The file layout shared by the static-site and S3 targets:
+//!
+//! - `d/<slug>/index.html`: the public page
+//! - `d/<slug>/disclosure.json`, `d/<slug>/hashes.json` and
+//!   `d/<slug>/receipts/<file>`: the published files
+//! - `api/disclosures/<slug>`: the document `GET /api/disclosures/<slug>`
+//!   returns, so `disclose fetch` works against the served site
+
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::hashing::HashesJson;
+use crate::manifest::{DisclosureManifest, PublicationInfo};
+
+use super::{FetchedDisclosure, PublishResponse};
+
+pub(super) struct SiteFile {
+    /// Relative to the site root, always with `/` separators.
+    pub path: String,
+    pub bytes: Vec<u8>,
+    pub content_type: &'static str,
+}
+
+pub(super) fn page_dir(slug: &str) -> String {
+    format!("d/{}/", slug)
+}
+
+pub(super) fn api_path(slug: &str) -> String {
+    format!("api/disclosures/{}", slug)
+}
+
+/// Receipts keep only their file name, so a crafted name cannot write
+/// outside the publication.
+fn receipt_path(slug: &str, filename: &str) -> Option<String> {
+    let name = Path::new(filename).file_name()?.to_str()?;
+    Some(format!("{}receipts/{}", page_dir(slug), name))
+}
+
+/// The publication block for `slug`, continuing the revisions of the API
+/// document already there, as the web API does.
+pub(super) fn next_publication(
+    slug: &str,
+    url: String,
+    previous: Option<&Value>,
+) -> PublicationInfo {
+    let previous = previous
+        .and_then(|document| document.pointer("/manifest/publication"))
+        .and_then(|publication| {
+            serde_json::from_value::<PublicationInfo>(publication.clone()).ok()
+        });
+    let now = Utc::now().to_rfc3339();
+    let (published_at, updated_at, revision) = match previous {
+        Some(previous) => (
+            previous.published_at,
+            Some(now),
+            previous.revision.unwrap_or(1) + 1,
+        ),
+        None => (Some(now), None, 1),
+    };
+    PublicationInfo {
+        slug: Some(slug.to_string()),
+        url: Some(url),
+        published_at,
+        updated_at,
+        revision: Some(revision),
+        target: None,
+        extra: Default::default(),
+    }
+}
+
+pub(super) fn response(slug: &str, publication: &PublicationInfo) -> PublishResponse {
+    PublishResponse {
+        slug: slug.to_string(),
+        url: publication.url.clone().unwrap_or_default(),
+        revision: publication.revision,
+    }
+}
+
+fn pretty(value: &Value) -> Result<Vec<u8>> {
+    let mut bytes = serde_json::to_vec_pretty(value)?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+/// Every file for a publish request `body` at `slug`.
+pub(super) fn site_files(
+    slug: &str,
+    body: &str,
+    publication: &PublicationInfo,
+) -> Result<Vec<SiteFile>> {
+    let FetchedDisclosure {
+        mut manifest,
+        hashes,
+        receipts,
+    } = serde_json::from_str(body)?;
+    manifest["publication"] = serde_json::to_value(publication)?;
+
+    let mut files = Vec::new();
+    let mut receipt_names = Vec::new();
+    for receipt in &receipts {
+        let Some(path) = receipt_path(slug, &receipt.filename) else {
+            continue;
+        };
+        receipt_names.push(path.rsplit('/').next().unwrap_or_default().to_string());
+        files.push(SiteFile {
+            path,
+            bytes: general_purpose::STANDARD.decode(&receipt.bytes_base64)?,
+            content_type: "application/octet-stream",
+        });
+    }
+    let page = render_page(
+        &serde_json::from_value(manifest.clone())?,
+        &serde_json::from_value(hashes.clone())?,
+        &receipt_names,
+    );
+    files.push(SiteFile {
+        path: format!("{}index.html", page_dir(slug)),
+        bytes: page.into_bytes(),
+        content_type: "text/html; charset=utf-8",
+    });
+    files.push(SiteFile {
+        path: format!("{}disclosure.json", page_dir(slug)),
+        bytes: pretty(&manifest)?,
+        content_type: "application/json",
+    });
+    files.push(SiteFile {
+        path: format!("{}hashes.json", page_dir(slug)),
+        bytes: pretty(&hashes)?,
+        content_type: "application/json",
+    });
+    // Written last: its presence marks a complete publication.
+    files.push(SiteFile {
+        path: api_path(slug),
+        bytes: serde_json::to_vec(&json!({
+            "manifest": manifest,
+            "hashes": hashes,
+            "receipts": receipts,
+        }))?,
+        content_type: "application/json",
+    });
+    Ok(files)
+}
+
+/// Every file of the publication described by its API `document`, for
+/// removal. The API document comes last so an interrupted removal can be
+/// retried.
+pub(super) fn published_paths(slug: &str, document: &Value) -> Vec<String> {
+    let mut paths: Vec<String> = document
+        .get("receipts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|receipt| receipt.get("filename").and_then(Value::as_str))
+        .filter_map(|filename| receipt_path(slug, filename))
+        .collect();
+    for name in ["index.html", "disclosure.json", "hashes.json"] {
+        paths.push(format!("{}{}", page_dir(slug), name));
+    }
+    paths.push(api_path(slug));
+    paths
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;line-height:1.5;margin:0;color:#1b1b1f}\
+main{max-width:44rem;margin:0 auto;padding:2rem 1rem}\
+table{border-collapse:collapse;width:100%}th,td{text-align:left;padding:.3rem .5rem;border-bottom:1px solid #ddd}\
+code{font-size:.85em;word-break:break-all}.meta{color:#555}";
+
+/// A self-contained page with the split, stages, tools, proof hashes and
+/// links to the JSON files. Every manifest value is escaped.
+fn render_page(manifest: &DisclosureManifest, hashes: &HashesJson, receipts: &[String]) -> String {
+    let title = escape(&manifest.project.title);
+    let mut out = format!(
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+<title>{} · AI disclosure</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n<h1>{}</h1>\n",
+        title, STYLE, title
+    );
+    let mut meta = Vec::new();
+    if let Some(author) = &manifest.project.author {
+        meta.push(format!("By {}", escape(author)));
+    }
+    if let Some(publication) = &manifest.publication {
+        if let Some(published_at) = &publication.published_at {
+            meta.push(format!("Published {}", escape(published_at)));
+        }
+        if let Some(revision) = publication.revision {
+            meta.push(format!("Revision {}", revision));
+        }
+    }
+    if !meta.is_empty() {
+        let _ = writeln!(out, "<p class=\"meta\">{}</p>", meta.join(" · "));
+    }
+
+    let global = &manifest.assistance.global;
+    let _ = writeln!(
+        out,
+        "<h2>AI assistance</h2>\n<p><strong>{}% human</strong>, {}% AI</p>",
+        global.human_percent, global.ai_percent
+    );
+    if let Some(stages) = manifest
+        .assistance
+        .stages
+        .as_ref()
+        .filter(|s| !s.is_empty())
+    {
+        out.push_str("<table>\n<tr><th>Stage</th><th>Grade</th><th>Approx. AI</th></tr>\n");
+        for stage in stages {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}%</td></tr>",
+                escape(&stage.label),
+                escape(stage.grade.as_str()),
+                stage.approx_ai_percent
+            );
+        }
+        out.push_str("</table>\n");
+    }
+    if let Some(notes) = &manifest.assistance.notes {
+        let _ = writeln!(out, "<p>{}</p>", escape(notes));
+    }
+    if let Some(tools) = manifest.ai_tools.as_ref().filter(|t| !t.is_empty()) {
+        out.push_str("<h2>Tools</h2>\n<ul>\n");
+        for tool in tools {
+            let detail: Vec<&str> = [tool.provider.as_deref(), tool.model.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            let _ = write!(out, "<li>{}", escape(&tool.name));
+            if !detail.is_empty() {
+                let _ = write!(out, " ({})", escape(&detail.join(", ")));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("<h2>Proof</h2>\n");
+    if manifest.proof.items.is_empty() {
+        out.push_str("<p>No proof items.</p>\n");
+    } else {
+        out.push_str("<table>\n<tr><th>Item</th><th>SHA-256</th></tr>\n");
+        for item in &manifest.proof.items {
+            let sha256 = hashes
+                .proof
+                .iter()
+                .find(|entry| entry.id == item.id)
+                .map(|entry| entry.sha256.as_str())
+                .unwrap_or("");
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td><code>{}</code></td></tr>",
+                escape(&item.label),
+                escape(sha256)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    let _ = write!(
+        out,
+        "<h2>Verification</h2>\n<p>Manifest SHA-256 <code>{}</code><br>\
+Bundle root SHA-256 <code>{}</code></p>\n\
+<p><a href=\"disclosure.json\">disclosure.json</a> · <a href=\"hashes.json\">hashes.json</a>",
+        escape(&hashes.manifest_sha256),
+        escape(&hashes.bundle_root_sha256)
+    );
+    for name in receipts {
+        let _ = write!(
+            out,
+            " · <a href=\"receipts/{}\">{}</a>",
+            escape(name),
+            escape(name)
+        );
+    }
+    out.push_str("</p>\n</main>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receipt_names_stay_inside_the_publication() {
+        assert_eq!(
+            receipt_path("abc", "../../etc/passwd").as_deref(),
+            Some("d/abc/receipts/passwd")
+        );
+        assert_eq!(receipt_path("abc", ".."), None);
+        assert_eq!(
+            escape("<b>\"x\" & 'y'</b>"),
+            "&lt;b&gt;&quot;x&quot; &amp; &#39;y&#39;&lt;/b&gt;"
+        );
+    }
+}
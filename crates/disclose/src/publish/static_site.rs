@@ -0,0 +1,115 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ValidationError;
+
+use super::site::{api_path, next_publication, page_dir, published_paths, response, site_files};
+use super::{new_slug, PublishResponse, PublishTarget, TargetKind};
+
+/// Writes publications into a local directory that any static host can
+/// serve. Updates replace the files in place and bump the revision.
+pub struct StaticSiteTarget {
+    dir: PathBuf,
+    /// Public address of `dir`, used for publication URLs.
+    base_url: Option<String>,
+}
+
+impl StaticSiteTarget {
+    pub fn new(dir: impl Into<PathBuf>, base_url: Option<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            base_url,
+        }
+    }
+
+    fn file(&self, path: &str) -> PathBuf {
+        self.dir.join(path)
+    }
+
+    /// The public page URL, or a `file://` URL when no base URL is set.
+    fn url(&self, slug: &str) -> Result<String> {
+        Ok(match &self.base_url {
+            Some(base) => format!("{}/{}", base.trim_end_matches('/'), page_dir(slug)),
+            None => format!(
+                "file://{}",
+                std::path::absolute(self.file(&page_dir(slug)).join("index.html"))?.display()
+            ),
+        })
+    }
+
+    fn read_document(&self, slug: &str) -> Result<Option<Value>> {
+        match fs::read_to_string(self.file(&api_path(slug))) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+fn remove_if_present(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+#[async_trait]
+impl PublishTarget for StaticSiteTarget {
+    fn kind(&self) -> TargetKind {
+        TargetKind::Static
+    }
+
+    fn describe(&self, slug: Option<&str>) -> (String, String) {
+        let dir = page_dir(slug.unwrap_or("<new slug>"));
+        ("WRITE".to_string(), self.file(&dir).display().to_string())
+    }
+
+    async fn publish(
+        &self,
+        slug: Option<&str>,
+        body: String,
+        _token: Option<String>,
+    ) -> Result<PublishResponse> {
+        let slug = slug.map(str::to_string).unwrap_or_else(new_slug);
+        let previous = self.read_document(&slug)?;
+        let publication = next_publication(&slug, self.url(&slug)?, previous.as_ref());
+        let files = site_files(&slug, &body, &publication)?;
+
+        // Receipts dropped since the last revision must not linger.
+        let receipts = self.file(&page_dir(&slug)).join("receipts");
+        if receipts.exists() {
+            fs::remove_dir_all(&receipts)?;
+        }
+        for file in files {
+            let path = self.file(&file.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, file.bytes)?;
+        }
+        Ok(response(&slug, &publication))
+    }
+
+    async fn unpublish(&self, slug: &str, _token: Option<String>) -> Result<()> {
+        let document = self.read_document(slug)?.ok_or_else(|| {
+            ValidationError::new(format!(
+                "Publication {} is not in {}",
+                slug,
+                self.dir.display()
+            ))
+        })?;
+        for path in published_paths(slug, &document) {
+            remove_if_present(&self.file(&path))?;
+        }
+        // Only directories left empty are removed, so files added by hand
+        // next to the publication survive.
+        let page = self.file(&page_dir(slug));
+        let _ = fs::remove_dir(page.join("receipts"));
+        let _ = fs::remove_dir(&page);
+        Ok(())
+    }
+}
@@ -1,46 +1,521 @@
 use anyhow::{anyhow, Result};
 use include_dir::{include_dir, Dir};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ValidationError;
+use crate::manifest::{AssistanceGrade, AssistanceStage, PercentRange};
+use crate::paths::user_config_dir;
+use crate::validation::validate_template_value;
+use crate::version::compare_versions;
 
 static TEMPLATES_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../../shared/templates");
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateStage {
     pub key: String,
     pub label: String,
+    /// Relative share of the work, used to derive the global split from
+    /// stage grades. Defaults to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+impl TemplateStage {
+    pub fn weight(&self) -> f64 {
+        self.weight.unwrap_or(1.0)
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Where a template was loaded from. Built-in templates are embedded at build
+/// time from `shared/templates`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "path", rename_all = "snake_case")]
+pub enum TemplateSource {
+    #[default]
+    Builtin,
+    User(PathBuf),
+    Workspace(PathBuf),
+    File(PathBuf),
+}
+
+/// One step of a grade scale. `percent` is the value recorded as
+/// `approx_ai_percent`; without it the midpoint of the range is used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GradeLevel {
+    pub key: String,
+    pub label: String,
+    pub min_percent: i32,
+    pub max_percent: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<i32>,
+}
+
+impl GradeLevel {
+    fn new(key: &str, label: &str, min_percent: i32, max_percent: i32, percent: i32) -> Self {
+        Self {
+            key: key.to_string(),
+            label: label.to_string(),
+            min_percent,
+            max_percent,
+            percent: Some(percent),
+        }
+    }
+
+    pub fn point_percent(&self) -> i32 {
+        self.percent
+            .unwrap_or((self.min_percent + self.max_percent) / 2)
+    }
+
+    pub fn range(&self) -> PercentRange {
+        PercentRange {
+            min: self.min_percent,
+            max: self.max_percent,
+            extra: Default::default(),
+        }
+    }
+
+    /// A manifest stage recorded at this grade.
+    pub fn stage(&self, key: &str, label: &str) -> AssistanceStage {
+        AssistanceStage {
+            key: key.to_string(),
+            label: label.to_string(),
+            grade: AssistanceGrade::new(self.key.clone()),
+            approx_ai_percent: self.point_percent(),
+            ai_percent_range: Some(self.range()),
+            extra: Default::default(),
+        }
+    }
+
+    pub fn contains(&self, percent: i32) -> bool {
+        (self.min_percent..=self.max_percent).contains(&percent)
+    }
+}
+
+/// The scale used by templates that do not declare `grades`. Point values
+/// match what the CLI recorded before scales were configurable.
+pub fn default_grade_scale() -> Vec<GradeLevel> {
+    vec![
+        GradeLevel::new("none", "None", 0, 0, 0),
+        GradeLevel::new("light", "Light", 1, 20, 10),
+        GradeLevel::new("moderate", "Moderate", 21, 45, 30),
+        GradeLevel::new("heavy", "Heavy", 46, 75, 60),
+        GradeLevel::new("full", "Full", 76, 100, 90),
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
     pub slug: String,
     pub version: String,
     pub label: String,
-    #[allow(dead_code)]
     #[serde(default)]
     pub recommended_proof: Vec<String>,
     pub stages: Vec<TemplateStage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grades: Option<Vec<GradeLevel>>,
+    #[serde(skip_deserializing)]
+    pub source: TemplateSource,
 }
 
-pub fn load_templates() -> Result<Vec<Template>> {
+impl Template {
+    /// Grades from least to most AI assistance.
+    pub fn grade_scale(&self) -> Vec<GradeLevel> {
+        self.grades.clone().unwrap_or_else(default_grade_scale)
+    }
+
+    pub fn grade(&self, key: &str) -> Result<GradeLevel> {
+        let scale = self.grade_scale();
+        if let Some(level) = scale.iter().find(|level| level.key == key) {
+            return Ok(level.clone());
+        }
+        let keys: Vec<&str> = scale.iter().map(|level| level.key.as_str()).collect();
+        Err(ValidationError::new(format!(
+            "Unknown grade {} for template {}. Available grades: {}",
+            key,
+            self.slug,
+            keys.join(", ")
+        ))
+        .into())
+    }
+
+    /// The grade whose range holds `percent`, used when moving stages
+    /// between scales.
+    pub fn grade_for_percent(&self, percent: i32) -> GradeLevel {
+        let scale = self.grade_scale();
+        scale
+            .iter()
+            .find(|level| level.contains(percent))
+            .or_else(|| {
+                scale
+                    .iter()
+                    .min_by_key(|level| (level.point_percent() - percent).abs())
+            })
+            .cloned()
+            .unwrap_or_else(|| default_grade_scale().remove(0))
+    }
+
+    pub fn lowest_grade(&self) -> GradeLevel {
+        self.grade_scale().remove(0)
+    }
+
+    /// AI share implied by the stage grades, weighted by the template's stage
+    /// weights. Stages the template does not know count with weight 1.
+    pub fn weighted_ai_percent(&self, stages: &[AssistanceStage]) -> Option<i32> {
+        let mut total = 0.0;
+        let mut weighted = 0.0;
+        for stage in stages {
+            let weight = self
+                .stages
+                .iter()
+                .find(|template_stage| template_stage.key == stage.key)
+                .map(TemplateStage::weight)
+                .unwrap_or(1.0);
+            total += weight;
+            weighted += weight * f64::from(stage.approx_ai_percent);
+        }
+        (total > 0.0).then(|| (weighted / total).round() as i32)
+    }
+}
+
+/// Parses and validates a template document against the template schema.
+pub fn parse_template(content: &str, source: TemplateSource) -> Result<Template> {
+    let value: serde_json::Value = serde_json::from_str(content)?;
+    validate_template_value(&value)?;
+    let mut template: Template = serde_json::from_value(value)?;
+    let mut seen = std::collections::HashSet::new();
+    for stage in &template.stages {
+        if !seen.insert(stage.key.as_str()) {
+            return Err(ValidationError::new(format!(
+                "Template {} has duplicate stage key {}",
+                template.slug, stage.key
+            ))
+            .into());
+        }
+    }
+    if let Some(grades) = &template.grades {
+        let mut keys = std::collections::HashSet::new();
+        for level in grades {
+            if !keys.insert(level.key.as_str()) {
+                return Err(ValidationError::new(format!(
+                    "Template {} has duplicate grade key {}",
+                    template.slug, level.key
+                ))
+                .into());
+            }
+            let point = level.point_percent();
+            if level.min_percent > level.max_percent || !level.contains(point) {
+                return Err(ValidationError::new(format!(
+                    "Template {} grade {} has an invalid range {}-{} (percent {})",
+                    template.slug, level.key, level.min_percent, level.max_percent, point
+                ))
+                .into());
+            }
+        }
+    }
+    template.source = source;
+    Ok(template)
+}
+
+pub fn load_template_file(path: &Path, source: TemplateSource) -> Result<Template> {
+    let content = fs::read_to_string(path)?;
+    parse_template(&content, source).map_err(|err| {
+        let message = format!("{}: {}", path.display(), err);
+        if err.downcast_ref::<ValidationError>().is_some() {
+            ValidationError::new(message).into()
+        } else {
+            anyhow!(message)
+        }
+    })
+}
+
+/// Built-in templates, including retired versions kept in subfolders such as
+/// `shared/templates/archive/` so pinned workspaces keep resolving.
+fn builtin_templates() -> Result<Vec<Template>> {
     let mut templates = Vec::new();
-    for file in TEMPLATES_DIR.files() {
-        if let Some(ext) = file.path().extension() {
-            if ext == "json" {
-                let content = file
-                    .contents_utf8()
-                    .ok_or_else(|| anyhow!("Invalid UTF-8"))?;
-                let template: Template = serde_json::from_str(content)?;
-                templates.push(template);
+    let mut dirs = vec![&TEMPLATES_DIR];
+    while let Some(dir) = dirs.pop() {
+        dirs.extend(dir.dirs());
+        for file in dir.files() {
+            if let Some(ext) = file.path().extension() {
+                if ext == "json" {
+                    let content = file
+                        .contents_utf8()
+                        .ok_or_else(|| anyhow!("Invalid UTF-8"))?;
+                    templates.push(parse_template(content, TemplateSource::Builtin)?);
+                }
             }
         }
     }
     Ok(templates)
 }
 
-pub fn get_template(slug: &str) -> Result<Template> {
-    let templates = load_templates()?;
-    templates
-        .into_iter()
-        .find(|template| template.slug == slug)
-        .ok_or_else(|| anyhow!("Unknown template"))
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+pub fn user_templates_dir() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("templates"))
+}
+
+/// Every template visible from one place: built-ins, the user config
+/// directory, a workspace-local `templates/` folder and explicit files.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateCatalog {
+    templates: Vec<Template>,
+    skipped: Vec<(PathBuf, String)>,
+}
+
+impl TemplateCatalog {
+    pub fn discover(workspace_root: Option<&Path>) -> Result<Self> {
+        let mut catalog = Self {
+            templates: builtin_templates()?,
+            skipped: Vec::new(),
+        };
+        if let Some(dir) = user_templates_dir() {
+            for path in json_files(&dir)? {
+                catalog.add_discovered(&path, TemplateSource::User(path.clone()));
+            }
+        }
+        if let Some(root) = workspace_root {
+            for path in json_files(&root.join("templates"))? {
+                catalog.add_discovered(&path, TemplateSource::Workspace(path.clone()));
+            }
+        }
+        Ok(catalog)
+    }
+
+    fn add_discovered(&mut self, path: &Path, source: TemplateSource) {
+        let loaded = load_template_file(path, source).and_then(|template| {
+            self.check_builtin_collision(&template, path)?;
+            Ok(template)
+        });
+        match loaded {
+            Ok(template) => self.templates.push(template),
+            Err(err) => self.skipped.push((path.to_path_buf(), err.to_string())),
+        }
+    }
+
+    /// Adds a template passed explicitly, e.g. via `--template-file`. Invalid
+    /// files are an error rather than being skipped.
+    pub fn add_file(&mut self, path: &Path) -> Result<Template> {
+        let template = load_template_file(path, TemplateSource::File(path.to_path_buf()))?;
+        self.check_builtin_collision(&template, path)?;
+        self.templates.push(template.clone());
+        Ok(template)
+    }
+
+    /// A template may reuse a built-in's slug and version only as an exact
+    /// copy, such as the one `init` pins into a workspace. A changed copy
+    /// would otherwise silently lose to the built-in.
+    fn check_builtin_collision(&self, template: &Template, path: &Path) -> Result<()> {
+        let builtin = self.templates.iter().find(|builtin| {
+            builtin.source == TemplateSource::Builtin
+                && builtin.slug == template.slug
+                && compare_versions(&builtin.version, &template.version).is_eq()
+        });
+        match builtin {
+            Some(builtin) if !same_content(builtin, template)? => {
+                Err(ValidationError::new(format!(
+                    "{}: template {} {} differs from the built-in {} {}. Bump its version to use it instead",
+                    path.display(),
+                    template.slug,
+                    template.version,
+                    builtin.slug,
+                    builtin.version
+                ))
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn skipped(&self) -> &[(PathBuf, String)] {
+        &self.skipped
+    }
+
+    /// One template per slug: the highest version wins, see [`prefer`].
+    pub fn latest(&self) -> Vec<Template> {
+        let mut best: Vec<Template> = Vec::new();
+        for template in &self.templates {
+            match best.iter_mut().find(|t| t.slug == template.slug) {
+                Some(current) => {
+                    if prefer(template, current) {
+                        *current = template.clone();
+                    }
+                }
+                None => best.push(template.clone()),
+            }
+        }
+        best.sort_by(|a, b| a.label.cmp(&b.label));
+        best
+    }
+
+    pub fn get(&self, slug: &str) -> Result<Template> {
+        let latest = self.latest();
+        if let Some(template) = latest.iter().find(|template| template.slug == slug) {
+            return Ok(template.clone());
+        }
+        let slugs: Vec<&str> = latest.iter().map(|t| t.slug.as_str()).collect();
+        let close = close_matches(slug, &slugs);
+        let hint = if close.is_empty() {
+            format!("Available templates: {}", slugs.join(", "))
+        } else {
+            format!("Did you mean: {}?", close.join(", "))
+        };
+        Err(ValidationError::new(format!("Unknown template {}. {}", slug, hint)).into())
+    }
+
+    /// Every known version of a template, oldest first.
+    pub fn versions(&self, slug: &str) -> Vec<Template> {
+        let mut versions: Vec<Template> = Vec::new();
+        for template in self.templates.iter().filter(|t| t.slug == slug) {
+            match versions.iter_mut().find(|t| t.version == template.version) {
+                Some(current) => {
+                    if prefer(template, current) {
+                        *current = template.clone();
+                    }
+                }
+                None => versions.push(template.clone()),
+            }
+        }
+        versions.sort_by(|a, b| compare_versions(&a.version, &b.version));
+        versions
+    }
+
+    /// The exact template version a workspace was created with.
+    pub fn get_version(&self, slug: &str, version: &str) -> Result<Template> {
+        let latest = self.get(slug)?;
+        self.versions(slug)
+            .into_iter()
+            .find(|template| compare_versions(&template.version, version).is_eq())
+            .ok_or_else(|| {
+                ValidationError::new(format!(
+                    "Template {} version {} is not available (latest is {}). Run `disclose templates upgrade` to move this workspace to a newer version",
+                    slug, version, latest.version
+                ))
+                .into()
+            })
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Candidates within a small edit distance of `input`, or sharing a prefix
+/// with it, closest first.
+pub fn close_matches<'a>(input: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+    let input = input.to_lowercase();
+    let mut scored: Vec<(usize, &str)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&input, candidate);
+            let related = candidate.starts_with(&input) || input.starts_with(*candidate);
+            (distance <= 2.max(input.len() / 3) || related).then_some((distance, *candidate))
+        })
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+fn source_rank(source: &TemplateSource) -> u8 {
+    match source {
+        TemplateSource::Builtin => 3,
+        TemplateSource::File(_) => 2,
+        TemplateSource::Workspace(_) => 1,
+        TemplateSource::User(_) => 0,
+    }
+}
+
+/// Higher versions win; for the same version, built-ins beat explicit files,
+/// which beat workspace templates, which beat the user config directory.
+/// Other sources can only tie with a built-in by being an identical copy,
+/// see [`TemplateCatalog::check_builtin_collision`].
+fn prefer(candidate: &Template, current: &Template) -> bool {
+    match compare_versions(&candidate.version, &current.version) {
+        Ordering::Greater => true,
+        Ordering::Less => false,
+        Ordering::Equal => source_rank(&candidate.source) > source_rank(&current.source),
+    }
+}
+
+/// Compares everything but where the templates were loaded from.
+fn same_content(a: &Template, b: &Template) -> Result<bool> {
+    let content = |template: &Template| -> Result<serde_json::Value> {
+        let mut value = serde_json::to_value(template)?;
+        if let Some(map) = value.as_object_mut() {
+            map.remove("source");
+        }
+        Ok(value)
+    };
+    Ok(content(a)? == content(b)?)
+}
+
+pub fn load_templates() -> Result<Vec<Template>> {
+    Ok(TemplateCatalog::discover(None)?.latest())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_matches_ranks_typos() {
+        let slugs = ["article", "code", "podcast"];
+        assert_eq!(close_matches("artcle", &slugs), vec!["article"]);
+        assert_eq!(close_matches("cod", &slugs), vec!["code"]);
+        assert!(close_matches("spreadsheet", &slugs).is_empty());
+    }
+
+    #[test]
+    fn builtins_win_version_ties_and_reject_changed_copies() {
+        let json = r#"{ "slug": "code", "version": "1.0.0", "label": "Code",
+            "recommended_proof": [], "stages": [{ "key": "custom", "label": "Custom" }] }"#;
+        let builtin = parse_template(json, TemplateSource::Builtin).expect("builtin");
+        let path = PathBuf::from("code.json");
+        let copy = parse_template(json, TemplateSource::Workspace(path.clone())).expect("copy");
+        assert!(prefer(&builtin, &copy));
+        assert!(!prefer(&copy, &builtin));
+
+        let catalog = TemplateCatalog {
+            templates: vec![builtin],
+            skipped: Vec::new(),
+        };
+        assert!(catalog.check_builtin_collision(&copy, &path).is_ok());
+        let changed = json.replace("Custom", "Renamed");
+        let changed =
+            parse_template(&changed, TemplateSource::Workspace(path.clone())).expect("changed");
+        let err = catalog
+            .check_builtin_collision(&changed, &path)
+            .expect_err("collision");
+        assert!(err.to_string().contains("Bump its version"));
+        let bumped = json.replace("Custom", "Renamed").replace("1.0.0", "1.0.1");
+        let bumped =
+            parse_template(&bumped, TemplateSource::Workspace(path.clone())).expect("bumped");
+        assert!(catalog.check_builtin_collision(&bumped, &path).is_ok());
+    }
 }
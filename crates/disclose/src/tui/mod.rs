@@ -14,10 +14,15 @@ use std::io::{self, Stdout};
 use std::path::PathBuf;
 
 use crate::commands::{
-    attach_proof, export_bundle, init_workspace, publish_workspace, stamp_workspace, update_meter,
-    ExportFormat, IncludeProof,
+    attach_proof, export_bundle, init_workspace, publish_workspace, scan_export, stamp_workspace,
+    update_meter, ExportFormat, IncludeProof, SplitMode, DEFAULT_SPLIT_TOLERANCE,
 };
+use crate::credentials::{resolve_token, save_token};
+use crate::manifest::DisclosureManifest;
+use crate::pii::{format_findings, Finding};
+use crate::publish::{ApiTarget, HttpOptions};
 use crate::templates::{load_templates, Template};
+use crate::workspace::Workspace;
 
 struct TuiTerminal {
     terminal: Terminal<CrosstermBackend<Stdout>>,
@@ -63,6 +68,24 @@ fn read_text(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     title: &str,
     prompt: &str,
+) -> Result<String> {
+    read_input(terminal, title, prompt, false)
+}
+
+/// Like [`read_text`], but shows `*` instead of what is typed.
+fn read_secret(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    title: &str,
+    prompt: &str,
+) -> Result<String> {
+    read_input(terminal, title, prompt, true)
+}
+
+fn read_input(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    title: &str,
+    prompt: &str,
+    masked: bool,
 ) -> Result<String> {
     let mut input = String::new();
     loop {
@@ -83,7 +106,12 @@ fn read_text(
                 .split(inner);
 
             frame.render_widget(Paragraph::new(prompt), chunks[0]);
-            let input_line = Paragraph::new(input.as_str())
+            let shown = if masked {
+                "*".repeat(input.chars().count())
+            } else {
+                input.clone()
+            };
+            let input_line = Paragraph::new(shown)
                 .style(Style::default().fg(Color::Yellow))
                 .block(Block::default().borders(Borders::ALL).title("Input"));
             frame.render_widget(input_line, chunks[1]);
@@ -111,13 +139,38 @@ fn read_bool(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     title: &str,
     prompt: &str,
+) -> Result<bool> {
+    read_yes_no(
+        terminal,
+        title,
+        vec![Line::from(prompt), Line::from("Press Y / N")],
+    )
+}
+
+/// Lists scanner findings and asks whether to go on. No findings means yes.
+fn confirm_findings(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    findings: &[Finding],
+) -> Result<bool> {
+    if findings.is_empty() {
+        return Ok(true);
+    }
+    let mut body: Vec<Line> = format_findings(findings)
+        .lines()
+        .map(|line| Line::from(line.to_string()))
+        .collect();
+    body.push(Line::from(""));
+    body.push(Line::from("Export and publish anyway? Press Y / N"));
+    read_yes_no(terminal, "Personal data", body)
+}
+
+fn read_yes_no(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    title: &str,
+    body: Vec<Line>,
 ) -> Result<bool> {
     loop {
-        draw_centered(
-            terminal,
-            title,
-            vec![Line::from(prompt), Line::from("Press Y / N")],
-        )?;
+        draw_centered(terminal, title, body.clone())?;
         if let Event::Key(key) = event::read()? {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => return Ok(true),
@@ -167,10 +220,8 @@ fn select_template(
                 KeyCode::Up => {
                     selected = selected.saturating_sub(1);
                 }
-                KeyCode::Down => {
-                    if selected + 1 < templates.len() {
-                        selected += 1;
-                    }
+                KeyCode::Down if selected + 1 < templates.len() => {
+                    selected += 1;
                 }
                 KeyCode::Enter => return Ok(templates[selected].clone()),
                 KeyCode::Esc => return Err(anyhow!("Cancelled")),
@@ -183,29 +234,36 @@ fn select_template(
 pub fn run_tui(root: PathBuf) -> Result<()> {
     let mut terminal = TuiTerminal::new()?;
 
-    let templates = load_templates()?;
-    let template = select_template(&mut terminal.terminal, &templates)?;
-    let title = read_text(&mut terminal.terminal, "Project Info", "Project title")?;
-    let author = read_text(&mut terminal.terminal, "Project Info", "Author (optional)")?;
-
-    let workspace_dir = if root.join("disclosure.json").exists() {
-        root
+    // Open an existing workspace at the root or in `./disclose`, the default
+    // of `disclose init --out`; otherwise create one there.
+    let existing = [root.clone(), root.join("disclose")]
+        .into_iter()
+        .map(Workspace::new)
+        .find(Workspace::exists);
+    let workspace = if let Some(workspace) = existing {
+        workspace
     } else {
-        root.join("disclose")
+        let templates = load_templates()?;
+        let template = select_template(&mut terminal.terminal, &templates)?;
+        let title = read_text(&mut terminal.terminal, "Project Info", "Project title")?;
+        let author = read_text(&mut terminal.terminal, "Project Info", "Author (optional)")?;
+        init_workspace(
+            root.join("disclose"),
+            Some(&template.slug),
+            None,
+            Some(&title),
+            if author.is_empty() {
+                None
+            } else {
+                Some(author)
+            },
+            Vec::new(),
+            false,
+            None,
+        )?
+        .workspace
     };
 
-    let workspace = init_workspace(
-        workspace_dir,
-        &template.slug,
-        &title,
-        if author.is_empty() {
-            None
-        } else {
-            Some(author)
-        },
-        Vec::new(),
-    )?;
-
     loop {
         let path = read_text(
             &mut terminal.terminal,
@@ -224,9 +282,40 @@ pub fn run_tui(root: PathBuf) -> Result<()> {
             Some(true),
             None,
             None,
+            None,
         )?;
     }
 
+    let manifest = DisclosureManifest::read_from(&workspace.disclosure_path())?;
+    if let Some(completeness) = manifest
+        .proof
+        .completeness
+        .filter(|completeness| !completeness.slots.is_empty())
+    {
+        let mut body = vec![
+            Line::from(format!(
+                "Recommended proof: {}% complete",
+                completeness.score_percent
+            )),
+            Line::from(""),
+        ];
+        for slot in &completeness.slots {
+            let (mark, color) = if slot.proof_ids.is_empty() {
+                ("[ ]", Color::Red)
+            } else {
+                ("[x]", Color::Green)
+            };
+            body.push(Line::from(vec![
+                Span::styled(mark, Style::default().fg(color)),
+                Span::raw(format!(" {}", slot.label)),
+            ]));
+        }
+        body.push(Line::from(""));
+        body.push(Line::from("Press any key to continue."));
+        draw_centered(&mut terminal.terminal, "Proof Checklist", body)?;
+        let _ = event::read()?;
+    }
+
     let human = read_text(
         &mut terminal.terminal,
         "Assistance Meter",
@@ -234,7 +323,15 @@ pub fn run_tui(root: PathBuf) -> Result<()> {
     )?
     .parse::<i32>()
     .unwrap_or(70);
-    update_meter(&workspace, Some(human), None, Vec::new(), false)?;
+    update_meter(
+        &workspace,
+        Some(human),
+        None,
+        Vec::new(),
+        false,
+        SplitMode::Warn,
+        DEFAULT_SPLIT_TOLERANCE,
+    )?;
 
     let stamp = read_bool(
         &mut terminal.terminal,
@@ -245,6 +342,14 @@ pub fn run_tui(root: PathBuf) -> Result<()> {
         stamp_workspace(&workspace, None, None, None, false, None)?;
     }
 
+    let findings = scan_export(&workspace, IncludeProof::Hashes, None)?;
+    if !confirm_findings(&mut terminal.terminal, &findings)? {
+        return finish(
+            terminal,
+            "Disclosure saved without exporting or publishing. Press any key to exit.",
+        );
+    }
+
     let export_path = read_text(
         &mut terminal.terminal,
         "Export",
@@ -261,6 +366,7 @@ pub fn run_tui(root: PathBuf) -> Result<()> {
         IncludeProof::Hashes,
         true,
         ExportFormat::Zip,
+        None,
     )?;
 
     let publish = read_bool(&mut terminal.terminal, "Publish", "Publish disclosure now?")?;
@@ -275,17 +381,39 @@ pub fn run_tui(root: PathBuf) -> Result<()> {
         } else {
             endpoint
         };
+        // Stored tokens and DISCLOSE_TOKEN are picked up by the API target.
+        let token = if resolve_token(&endpoint)?.is_none() {
+            let token = read_secret(
+                &mut terminal.terminal,
+                "Publish",
+                "Token (blank to publish without one)",
+            )?;
+            if !token.is_empty()
+                && read_bool(
+                    &mut terminal.terminal,
+                    "Publish",
+                    "Save this token for the endpoint?",
+                )?
+            {
+                save_token(&endpoint, &token)?;
+            }
+            (!token.is_empty()).then_some(token)
+        } else {
+            None
+        };
         let runtime = tokio::runtime::Runtime::new()?;
-        runtime.block_on(publish_workspace(&workspace, &endpoint, None, true))?;
+        // Any findings were confirmed before exporting.
+        let target = ApiTarget::new(endpoint, HttpOptions::default());
+        runtime.block_on(publish_workspace(
+            &workspace, &target, token, true, false, None, true,
+        ))?;
     }
 
-    draw_centered(
-        &mut terminal.terminal,
-        "Done",
-        vec![Line::from("Disclosure saved. Press any key to exit.")],
-    )?;
-    let _ = event::read()?;
+    finish(terminal, "Disclosure saved. Press any key to exit.")
+}
 
-    terminal.finish()?;
-    Ok(())
+fn finish(mut terminal: TuiTerminal, message: &str) -> Result<()> {
+    draw_centered(&mut terminal.terminal, "Done", vec![Line::from(message)])?;
+    let _ = event::read()?;
+    terminal.finish()
 }
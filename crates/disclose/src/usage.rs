@@ -0,0 +1,479 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ValidationError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageFormat {
+    Jsonl,
+    Aider,
+    Copilot,
+}
+
+impl std::str::FromStr for UsageFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "jsonl" => Ok(UsageFormat::Jsonl),
+            "aider" => Ok(UsageFormat::Aider),
+            "copilot" => Ok(UsageFormat::Copilot),
+            _ => Err(anyhow!("Invalid usage format")),
+        }
+    }
+}
+
+/// One assistant session read from a log. Prompt text is only held in memory
+/// for stage matching; nothing in here is serialized.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    pub tool: String,
+    pub provider: Option<String>,
+    pub tool_version: Option<String>,
+    pub models: Vec<String>,
+    pub prompts: Vec<String>,
+    pub started: Option<DateTime<Utc>>,
+    pub ended: Option<DateTime<Utc>>,
+}
+
+impl Session {
+    fn saw(&mut self, at: Option<DateTime<Utc>>) {
+        let Some(at) = at else {
+            return;
+        };
+        self.started = Some(self.started.map_or(at, |started| started.min(at)));
+        self.ended = Some(self.ended.map_or(at, |ended| ended.max(at)));
+    }
+
+    fn add_model(&mut self, model: &str) {
+        let model = model.trim();
+        if !model.is_empty() && !self.models.iter().any(|known| known == model) {
+            self.models.push(model.to_string());
+        }
+    }
+}
+
+pub fn provider_for_model(model: &str) -> Option<&'static str> {
+    let model = model.to_lowercase();
+    let model = model.rsplit('/').next().unwrap_or_default();
+    if ["gpt", "o1", "o3", "o4", "chatgpt", "text-davinci"]
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+    {
+        Some("OpenAI")
+    } else if model.contains("claude") {
+        Some("Anthropic")
+    } else if model.contains("gemini") {
+        Some("Google")
+    } else if model.contains("mistral") || model.contains("codestral") {
+        Some("Mistral")
+    } else if model.contains("llama") {
+        Some("Meta")
+    } else if model.contains("deepseek") {
+        Some("DeepSeek")
+    } else {
+        None
+    }
+}
+
+fn tool_for_provider(provider: Option<&str>) -> &'static str {
+    match provider {
+        Some("OpenAI") => "ChatGPT",
+        Some("Anthropic") => "Claude",
+        Some("Google") => "Gemini",
+        Some("Mistral") => "Le Chat",
+        _ => "AI assistant",
+    }
+}
+
+/// Accepts unix seconds or milliseconds, RFC 3339, or `YYYY-MM-DD HH:MM:SS`
+/// (taken as UTC).
+pub fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(number) => {
+            let raw = number.as_f64()?;
+            let millis = if raw > 1e12 { raw } else { raw * 1000.0 };
+            Utc.timestamp_millis_opt(millis as i64).single()
+        }
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .map(|at| at.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|naive| naive.and_utc())
+            }),
+        _ => None,
+    }
+}
+
+fn first_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    ["timestamp", "created_at", "create_time", "created"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(parse_timestamp))
+}
+
+/// Text of a chat message, whether `content` is a string or a list of
+/// Anthropic/OpenAI style blocks.
+fn message_text(message: &Value) -> String {
+    let content = message
+        .get("content")
+        .or_else(|| message.pointer("/content/parts"));
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|block| {
+                block
+                    .as_str()
+                    .or_else(|| block.get("text").and_then(Value::as_str))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(Value::Object(object)) => object
+            .get("parts")
+            .and_then(Value::as_array)
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn message_role(message: &Value) -> Option<&str> {
+    message
+        .get("role")
+        .or_else(|| message.pointer("/author/role"))
+        .and_then(Value::as_str)
+}
+
+fn message_model(message: &Value) -> Option<&str> {
+    message
+        .get("model")
+        .or_else(|| message.pointer("/metadata/model_slug"))
+        .and_then(Value::as_str)
+}
+
+fn read_message(session: &mut Session, message: &Value) {
+    if let Some(model) = message_model(message) {
+        session.add_model(model);
+    }
+    session.saw(first_timestamp(message));
+    if message_role(message) == Some("user") {
+        session.prompts.push(message_text(message));
+    }
+}
+
+fn finish_session(mut session: Session, explicit_tool: Option<&str>) -> Session {
+    let provider = session
+        .models
+        .iter()
+        .find_map(|model| provider_for_model(model));
+    session.provider = provider.map(str::to_string);
+    session.tool = explicit_tool
+        .map(str::to_string)
+        .unwrap_or_else(|| tool_for_provider(provider).to_string());
+    session
+}
+
+fn explicit_tool(value: &Value) -> Option<&str> {
+    ["tool", "client", "source"]
+        .iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+}
+
+/// JSONL chat exports: either one conversation per line (with `messages`) or
+/// one message per line, grouped by a session or conversation id.
+pub fn parse_jsonl(content: &str) -> Result<Vec<Session>> {
+    let mut sessions = Vec::new();
+    let mut grouped: BTreeMap<String, (Session, Option<String>)> = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(line).map_err(|err| {
+            ValidationError::new(format!("line {} is not valid JSON: {}", index + 1, err))
+        })?;
+        if let Some(messages) = value.get("messages").and_then(Value::as_array) {
+            let mut session = Session::default();
+            if let Some(model) = message_model(&value) {
+                session.add_model(model);
+            }
+            session.saw(first_timestamp(&value));
+            for message in messages {
+                read_message(&mut session, message);
+            }
+            sessions.push(finish_session(session, explicit_tool(&value)));
+            continue;
+        }
+        let key = ["session_id", "conversation_id", "conversation", "thread_id"]
+            .iter()
+            .find_map(|key| value.get(*key).and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string();
+        let entry = grouped.entry(key).or_default();
+        if entry.1.is_none() {
+            entry.1 = explicit_tool(&value).map(str::to_string);
+        }
+        read_message(&mut entry.0, &value);
+    }
+    sessions.extend(
+        grouped
+            .into_values()
+            .map(|(session, tool)| finish_session(session, tool.as_deref())),
+    );
+    Ok(sessions)
+}
+
+/// aider's `.aider.chat.history.md`: sessions start with
+/// `# aider chat started at`, prompts are `####` lines and the model is
+/// announced in the `>` banner.
+pub fn parse_aider(content: &str) -> Result<Vec<Session>> {
+    let mut sessions: Vec<Session> = Vec::new();
+    for line in content.lines() {
+        if let Some(rest) = line.strip_prefix("# aider chat started at") {
+            let mut session = Session {
+                tool: "aider".to_string(),
+                ..Default::default()
+            };
+            session.saw(parse_timestamp(&Value::String(rest.trim().to_string())));
+            sessions.push(session);
+            continue;
+        }
+        let Some(session) = sessions.last_mut() else {
+            continue;
+        };
+        if let Some(version) = line.strip_prefix("> Aider v") {
+            session.tool_version = Some(version.trim().to_string());
+        } else if let Some(model) = line
+            .strip_prefix("> Main model:")
+            .or_else(|| line.strip_prefix("> Model:"))
+        {
+            let model = model.split(" with ").next().unwrap_or_default();
+            session.add_model(model);
+        } else if let Some(prompt) = line.strip_prefix("#### ") {
+            session.prompts.push(prompt.to_string());
+        }
+    }
+    if sessions.is_empty() {
+        return Err(ValidationError::new("no `# aider chat started at` header found").into());
+    }
+    Ok(sessions
+        .into_iter()
+        .map(|mut session| {
+            session.provider = session
+                .models
+                .iter()
+                .find_map(|model| provider_for_model(model))
+                .map(str::to_string);
+            session
+        })
+        .collect())
+}
+
+fn copilot_session(value: &Value) -> Session {
+    let mut session = Session {
+        tool: "GitHub Copilot".to_string(),
+        provider: Some("GitHub".to_string()),
+        ..Default::default()
+    };
+    for key in ["creationDate", "lastMessageDate"] {
+        session.saw(value.get(key).and_then(parse_timestamp));
+    }
+    if let Some(model) = value.get("modelId").and_then(Value::as_str) {
+        session.add_model(model.trim_start_matches("copilot/"));
+    }
+    for request in value
+        .get("requests")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        session.saw(request.get("timestamp").and_then(parse_timestamp));
+        let model = request
+            .get("modelId")
+            .or_else(|| request.pointer("/result/metadata/modelId"))
+            .and_then(Value::as_str);
+        if let Some(model) = model {
+            session.add_model(model.trim_start_matches("copilot/"));
+        }
+        if let Some(text) = request.pointer("/message/text").and_then(Value::as_str) {
+            session.prompts.push(text.to_string());
+        }
+    }
+    session
+}
+
+/// VS Code "Chat: Export Chat..." files: one session object with a
+/// `requests` array, or a list of them.
+pub fn parse_copilot(content: &str) -> Result<Vec<Session>> {
+    let value: Value = serde_json::from_str(content)
+        .map_err(|err| ValidationError::new(format!("not valid JSON: {}", err)))?;
+    let sessions: Vec<&Value> = match &value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    if sessions
+        .iter()
+        .any(|session| session.get("requests").and_then(Value::as_array).is_none())
+    {
+        return Err(ValidationError::new("expected a Copilot chat export with `requests`").into());
+    }
+    Ok(sessions.into_iter().map(copilot_session).collect())
+}
+
+pub fn detect_format(path: &Path, content: &str) -> Option<UsageFormat> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    if content.contains("# aider chat started at") {
+        Some(UsageFormat::Aider)
+    } else if extension == "jsonl" {
+        Some(UsageFormat::Jsonl)
+    } else if extension == "json" && content.contains("\"requests\"") {
+        Some(UsageFormat::Copilot)
+    } else {
+        None
+    }
+}
+
+pub fn parse_usage_file(path: &Path, format: Option<UsageFormat>) -> Result<Vec<Session>> {
+    let content = fs::read_to_string(path)?;
+    let format = format
+        .or_else(|| detect_format(path, &content))
+        .ok_or_else(|| {
+            ValidationError::new(format!(
+                "{}: unrecognised log format, pass --format jsonl|aider|copilot",
+                path.display()
+            ))
+        })?;
+    let parsed = match format {
+        UsageFormat::Jsonl => parse_jsonl(&content),
+        UsageFormat::Aider => parse_aider(&content),
+        UsageFormat::Copilot => parse_copilot(&content),
+    };
+    parsed.map_err(|err| ValidationError::new(format!("{}: {}", path.display(), err)).into())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolUsage {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub models: Vec<String>,
+    pub sessions: usize,
+    pub prompts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Normalized view of imported logs: counts and dates only, never prompt text.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSummary {
+    pub files: Vec<PathBuf>,
+    pub sessions: usize,
+    pub prompts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+    pub tools: Vec<ToolUsage>,
+}
+
+pub fn summarize(files: Vec<PathBuf>, sessions: &[Session]) -> UsageSummary {
+    let mut tools: Vec<ToolUsage> = Vec::new();
+    for session in sessions {
+        let index = match tools.iter().position(|tool| tool.name == session.tool) {
+            Some(index) => index,
+            None => {
+                tools.push(ToolUsage {
+                    name: session.tool.clone(),
+                    provider: None,
+                    version: None,
+                    models: Vec::new(),
+                    sessions: 0,
+                    prompts: 0,
+                    first_seen: None,
+                    last_seen: None,
+                });
+                tools.len() - 1
+            }
+        };
+        let tool = &mut tools[index];
+        tool.sessions += 1;
+        tool.prompts += session.prompts.len();
+        tool.provider = tool.provider.take().or_else(|| session.provider.clone());
+        if session.tool_version.is_some() {
+            tool.version = session.tool_version.clone();
+        }
+        for model in &session.models {
+            if !tool.models.contains(model) {
+                tool.models.push(model.clone());
+            }
+        }
+        if let Some(started) = session.started {
+            tool.first_seen = Some(tool.first_seen.map_or(started, |seen| seen.min(started)));
+        }
+        if let Some(ended) = session.ended {
+            tool.last_seen = Some(tool.last_seen.map_or(ended, |seen| seen.max(ended)));
+        }
+    }
+    UsageSummary {
+        files,
+        sessions: sessions.len(),
+        prompts: sessions.iter().map(|session| session.prompts.len()).sum(),
+        first_seen: tools.iter().filter_map(|tool| tool.first_seen).min(),
+        last_seen: tools.iter().filter_map(|tool| tool.last_seen).max(),
+        tools,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jsonl_groups_messages_by_conversation() {
+        let content = [
+            r#"{"conversation_id":"a","role":"user","content":"draft the intro","created_at":"2025-03-01T10:00:00Z"}"#,
+            r#"{"conversation_id":"a","role":"assistant","model":"gpt-4o","content":"..."}"#,
+            r#"{"conversation_id":"b","role":"user","content":[{"type":"text","text":"fix tests"}],"timestamp":1741428000}"#,
+            r#"{"conversation_id":"b","role":"assistant","model":"claude-3-5-sonnet","content":"..."}"#,
+        ]
+        .join("\n");
+        let sessions = parse_jsonl(&content).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].tool, "ChatGPT");
+        assert_eq!(sessions[1].provider.as_deref(), Some("Anthropic"));
+        assert_eq!(sessions[1].prompts, vec!["fix tests"]);
+    }
+
+    #[test]
+    fn aider_history_reads_banner_and_prompts() {
+        let content = "# aider chat started at 2025-02-01 09:30:00\n\n> Aider v0.50.1\n> Main model: claude-3-5-sonnet-20240620 with diff edit format\n\n#### add a parser\n\nSure.\n\n# aider chat started at 2025-02-02 11:00:00\n\n> Model: gpt-4o with diff edit format\n\n#### write tests\n";
+        let sessions = parse_aider(content).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].tool_version.as_deref(), Some("0.50.1"));
+        assert_eq!(sessions[0].models, vec!["claude-3-5-sonnet-20240620"]);
+        assert_eq!(sessions[1].provider.as_deref(), Some("OpenAI"));
+        let summary = summarize(Vec::new(), &sessions);
+        assert_eq!(summary.tools.len(), 1);
+        assert_eq!(summary.prompts, 2);
+    }
+}
@@ -3,18 +3,23 @@ use jsonschema::JSONSchema;
 use serde_json::Value;
 use std::sync::OnceLock;
 
+use crate::errors::ValidationError;
 use crate::manifest::DisclosureManifest;
+use crate::templates::Template;
+
+fn compile_schema(schema_str: &'static str) -> Result<JSONSchema, String> {
+    let schema: Value = serde_json::from_str(schema_str).map_err(|err| err.to_string())?;
+    let schema = Box::leak(Box::new(schema));
+    JSONSchema::compile(schema).map_err(|err| err.to_string())
+}
 
 pub fn validate_manifest(manifest: &DisclosureManifest) -> Result<()> {
     static SCHEMA: OnceLock<Result<JSONSchema, String>> = OnceLock::new();
     let compiled = SCHEMA.get_or_init(|| {
-        let schema_str = include_str!(concat!(
+        compile_schema(include_str!(concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/../../shared/schemas/disclosure.schema.json"
-        ));
-        let schema: Value = serde_json::from_str(schema_str).map_err(|err| err.to_string())?;
-        let schema = Box::leak(Box::new(schema));
-        JSONSchema::compile(schema).map_err(|err| err.to_string())
+        )))
     });
     let compiled = match compiled {
         Ok(schema) => schema,
@@ -27,3 +32,44 @@ pub fn validate_manifest(manifest: &DisclosureManifest) -> Result<()> {
     }
     Ok(())
 }
+
+pub fn validate_template_value(instance: &Value) -> Result<()> {
+    static SCHEMA: OnceLock<Result<JSONSchema, String>> = OnceLock::new();
+    let compiled = SCHEMA.get_or_init(|| {
+        compile_schema(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../shared/schemas/template.schema.json"
+        )))
+    });
+    let compiled = match compiled {
+        Ok(schema) => schema,
+        Err(err) => return Err(anyhow!(err.clone())),
+    };
+    if let Err(errors) = compiled.validate(instance) {
+        let messages: Vec<String> = errors
+            .map(|e| format!("{} at {}", e, e.instance_path))
+            .collect();
+        return Err(ValidationError::new(format!(
+            "template validation error: {}",
+            messages.join(", ")
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Checks stage grades against the template's grade scale: every grade must
+/// exist and `approx_ai_percent` must fall inside its range.
+pub fn validate_stage_grades(manifest: &DisclosureManifest, template: &Template) -> Result<()> {
+    for stage in manifest.assistance.stages.iter().flatten() {
+        let level = template.grade(stage.grade.as_str())?;
+        if !level.contains(stage.approx_ai_percent) {
+            return Err(ValidationError::new(format!(
+                "validation error: stage {} is graded {} ({}-{}%) but records {}% AI",
+                stage.key, level.key, level.min_percent, level.max_percent, stage.approx_ai_percent
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
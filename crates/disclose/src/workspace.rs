@@ -1,5 +1,16 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use crate::errors::ValidationError;
+
+/// Folder `disclose init` creates by default, also looked up during discovery.
+const DEFAULT_DIR: &str = "disclose";
+
+/// `status` in `.disclose/state.json` of a copy saved by `disclose fetch`.
+pub const FETCHED_STATUS: &str = "fetched";
+
 #[derive(Debug, Clone)]
 pub struct Workspace {
     pub root: PathBuf,
@@ -27,6 +38,10 @@ impl Workspace {
         self.root.join("proof")
     }
 
+    pub fn templates_dir(&self) -> PathBuf {
+        self.root.join("templates")
+    }
+
     pub fn state_dir(&self) -> PathBuf {
         self.root.join(".disclose")
     }
@@ -56,4 +71,176 @@ impl Workspace {
     pub fn root_path(&self) -> &Path {
         &self.root
     }
+
+    /// Where a fetched workspace was downloaded from; `None` for local ones.
+    pub fn fetched_from(&self) -> Option<String> {
+        let data = std::fs::read_to_string(self.state_path()).ok()?;
+        let state: serde_json::Value = serde_json::from_str(&data).ok()?;
+        (state["status"] == FETCHED_STATUS).then(|| {
+            state["source"]["url"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        })
+    }
+
+    /// Fails for fetched copies, which mirror a publication and must not be
+    /// edited, stamped or published.
+    pub fn ensure_writable(&self) -> Result<(), ValidationError> {
+        match self.fetched_from() {
+            Some(url) => Err(ValidationError::new(format!(
+                "This workspace is a read-only copy fetched from {}. Start an editable disclosure with `disclose init --from {}`",
+                url,
+                self.disclosure_path().display()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Named workspaces of a repository, stored in `.disclose/workspaces.json`
+/// with paths relative to the directory holding `.disclose/`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    pub workspaces: BTreeMap<String, PathBuf>,
+}
+
+impl WorkspaceRegistry {
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(".disclose").join("workspaces.json")
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let data = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&data).map_err(|err| anyhow!("Invalid {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir.join(".disclose"))?;
+        std::fs::write(Self::path(dir), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records `root` under `name`, relative to `dir` when possible.
+    pub fn register(dir: &Path, name: &str, root: &Path) -> Result<()> {
+        let mut registry = Self::load(dir)?;
+        let dir = std::path::absolute(dir)?;
+        let root = std::path::absolute(root)?;
+        let relative = pathdiff::diff_paths(&root, &dir).unwrap_or(root);
+        registry.workspaces.insert(name.to_string(), relative);
+        registry.save(&dir)
+    }
+}
+
+impl Workspace {
+    /// Finds the workspace for commands run from `start`, walking up parent
+    /// directories the way git does. In each directory it looks for
+    /// `disclosure.json`, then the named workspaces in `.disclose/`, then the
+    /// default `disclose/` folder. A `.disclose/` folder ends the search.
+    /// With `name`, only named workspaces (or a `<name>/` folder) match.
+    pub fn discover(start: &Path, name: Option<&str>) -> Result<Workspace> {
+        Self::search(start, name, true)
+    }
+
+    /// Like [`Workspace::discover`], but only looks in `dir` itself. Used
+    /// for an explicit `--path`, which must not resolve to a parent.
+    pub fn open(dir: &Path, name: Option<&str>) -> Result<Workspace> {
+        if !dir.is_dir() {
+            return Err(ValidationError::new(format!("{} does not exist", dir.display())).into());
+        }
+        Self::search(dir, name, false)
+    }
+
+    fn search(start: &Path, name: Option<&str>, walk_up: bool) -> Result<Workspace> {
+        let absolute = std::path::absolute(start)?;
+        let depth_limit = if walk_up { usize::MAX } else { 1 };
+        let mut known_names: Vec<String> = Vec::new();
+        for (depth, dir) in absolute.ancestors().take(depth_limit).enumerate() {
+            let display_dir = if depth == 0 { start } else { dir };
+            let registry = WorkspaceRegistry::load(dir)?;
+            match name {
+                Some(name) => {
+                    if let Some(path) = registry.workspaces.get(name) {
+                        return registered_workspace(name, display_dir.join(path));
+                    }
+                    let candidate = Workspace::new(display_dir.join(name));
+                    if candidate.exists() {
+                        return Ok(candidate);
+                    }
+                    known_names.extend(registry.workspaces.into_keys());
+                }
+                None => {
+                    let here = Workspace::new(display_dir.to_path_buf());
+                    if here.exists() {
+                        return Ok(here);
+                    }
+                    let mut registered = registry.workspaces.into_iter();
+                    match (registered.next(), registered.next()) {
+                        (Some((name, path)), None) => {
+                            return registered_workspace(&name, display_dir.join(path))
+                        }
+                        (Some((first, _)), Some((second, _))) => {
+                            let mut names = vec![first, second];
+                            names.extend(registered.map(|(name, _)| name));
+                            return Err(ValidationError::new(format!(
+                                "Several disclosures found in {}: {}. Pick one with --disclosure <name>",
+                                dir.display(),
+                                names.join(", ")
+                            ))
+                            .into());
+                        }
+                        _ => {}
+                    }
+                    let default = Workspace::new(display_dir.join(DEFAULT_DIR));
+                    if default.exists() {
+                        return Ok(default);
+                    }
+                }
+            }
+            if dir.join(".disclose").is_dir() {
+                break;
+            }
+        }
+        match name {
+            Some(name) if !known_names.is_empty() => Err(ValidationError::new(format!(
+                "No disclosure named {}. Known disclosures: {}",
+                name,
+                known_names.join(", ")
+            ))
+            .into()),
+            Some(name) if !walk_up => Err(anyhow!(
+                "No disclosure named {} in {}",
+                name,
+                start.display()
+            )),
+            Some(name) => Err(anyhow!(
+                "No disclosure named {} in {} or any parent directory",
+                name,
+                start.display()
+            )),
+            None if !walk_up => Err(anyhow!("disclosure.json not found in {}", start.display())),
+            None => Err(anyhow!(
+                "disclosure.json not found in {} or any parent directory",
+                start.display()
+            )),
+        }
+    }
+}
+
+/// The workspace registered as `name`, which must still be there.
+fn registered_workspace(name: &str, root: PathBuf) -> Result<Workspace> {
+    let workspace = Workspace::new(root);
+    if !workspace.exists() {
+        return Err(ValidationError::new(format!(
+            "Disclosure {} is registered at {}, but it has no disclosure.json there",
+            name,
+            workspace.root.display()
+        ))
+        .into());
+    }
+    Ok(workspace)
 }
@@ -1,11 +1,12 @@
 use assert_cmd::cargo::cargo_bin_cmd;
 use assert_cmd::Command;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
 use std::path::PathBuf;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 use tempfile::TempDir;
@@ -62,18 +63,19 @@ fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         .position(|window| window == needle)
 }
 
-fn parse_content_length(headers: &[u8]) -> Option<usize> {
+fn header_value(headers: &[u8], name: &str) -> Option<String> {
     let text = String::from_utf8_lossy(headers);
-    for line in text.lines() {
-        if line.to_ascii_lowercase().starts_with("content-length:") {
-            let value = line.split(':').nth(1).unwrap_or("").trim();
-            return value.parse().ok();
-        }
-    }
-    None
+    let prefix = format!("{}:", name.to_ascii_lowercase());
+    text.lines()
+        .find(|line| line.to_ascii_lowercase().starts_with(&prefix))
+        .map(|line| line[prefix.len()..].trim().to_string())
+}
+
+fn parse_content_length(headers: &[u8]) -> Option<usize> {
+    header_value(headers, "content-length")?.parse().ok()
 }
 
-fn read_http_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+fn read_http_request(stream: &mut TcpStream) -> (String, Option<String>, Vec<u8>) {
     let mut buffer = Vec::new();
     let mut headers_end = None;
     let mut content_length = None;
@@ -101,8 +103,8 @@ fn read_http_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
                 content_length = parse_content_length(&buffer[..pos + 4]);
             }
         }
-        if let (Some(end), Some(len)) = (headers_end, content_length) {
-            if buffer.len() >= end + len {
+        if let Some(end) = headers_end {
+            if buffer.len() >= end + content_length.unwrap_or(0) {
                 break;
             }
         }
@@ -118,10 +120,18 @@ fn read_http_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
     let headers = &buffer[..end];
     let headers_text = String::from_utf8_lossy(headers);
     let request_line = headers_text.lines().next().unwrap_or("").to_string();
-    (request_line, body)
+    (request_line, header_value(headers, "authorization"), body)
 }
 
-fn spawn_publish_server() -> (String, mpsc::Receiver<Value>, thread::JoinHandle<()>) {
+/// JSON body and `Authorization` header of a publish request.
+type PublishRequest = (Value, Option<String>);
+
+/// Accepts one publish request and sends it back over the channel.
+fn spawn_publish_server() -> (
+    String,
+    mpsc::Receiver<PublishRequest>,
+    thread::JoinHandle<()>,
+) {
     let listener = TcpListener::bind("127.0.0.1:0").expect("bind server");
     listener.set_nonblocking(true).expect("set nonblocking");
     let addr = listener.local_addr().expect("local addr");
@@ -133,7 +143,7 @@ fn spawn_publish_server() -> (String, mpsc::Receiver<Value>, thread::JoinHandle<
         loop {
             match listener.accept() {
                 Ok((mut stream, _)) => {
-                    let (request_line, body) = read_http_request(&mut stream);
+                    let (request_line, authorization, body) = read_http_request(&mut stream);
                     let mut parts = request_line.split_whitespace();
                     let method = parts.next().unwrap_or("");
                     let path = parts.next().unwrap_or("");
@@ -142,7 +152,7 @@ fn spawn_publish_server() -> (String, mpsc::Receiver<Value>, thread::JoinHandle<
 
                     let payload: Value =
                         serde_json::from_slice(&body).expect("publish payload json");
-                    let _ = tx.send(payload);
+                    let _ = tx.send((payload, authorization));
 
                     let response_body =
                         serde_json::json!({ "slug": "e2e-test", "url": "http://localhost:3000/d/e2e-test" })
@@ -169,6 +179,84 @@ fn spawn_publish_server() -> (String, mpsc::Receiver<Value>, thread::JoinHandle<
     (endpoint, rx, handle)
 }
 
+/// Method, path and `Authorization` header of a request to the API server.
+type ApiRequest = (String, String, Option<String>);
+
+/// Serves `count` publish API requests: POST creates `slug-1`, PUT bumps the
+/// revision of the slug in the path and DELETE succeeds.
+fn spawn_api_server(count: usize) -> (String, mpsc::Receiver<ApiRequest>, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind server");
+    let endpoint = format!("http://{}", listener.local_addr().expect("local addr"));
+    let (tx, rx) = mpsc::channel();
+    let base = endpoint.clone();
+
+    let handle = thread::spawn(move || {
+        let mut revision = 1;
+        for stream in listener.incoming().take(count) {
+            let mut stream = stream.expect("accept");
+            let (request_line, authorization, _) = read_http_request(&mut stream);
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or("").to_string();
+            let path = parts.next().unwrap_or("").to_string();
+            let response_body = match method.as_str() {
+                "POST" => serde_json::json!({ "slug": "slug-1", "url": format!("{}/d/slug-1", base), "revision": 1 }),
+                "PUT" => {
+                    revision += 1;
+                    let slug = path.rsplit('/').next().unwrap_or("");
+                    serde_json::json!({ "slug": slug, "url": format!("{}/d/{}", base, slug), "revision": revision })
+                }
+                _ => serde_json::json!({ "ok": true }),
+            }
+            .to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response_body.len(),
+                response_body
+            );
+            let _ = stream.write_all(response.as_bytes());
+            let _ = tx.send((method, path, authorization));
+        }
+    });
+
+    (endpoint, rx, handle)
+}
+
+/// Answers one GET per body, in order, and sends back each request path.
+fn spawn_get_server(
+    bodies: Vec<Value>,
+) -> (String, mpsc::Receiver<String>, thread::JoinHandle<()>) {
+    spawn_status_server(bodies.into_iter().map(|body| (200, body)).collect())
+}
+
+/// Answers one request per `(status, body)` pair and reports each request
+/// path.
+fn spawn_status_server(
+    responses: Vec<(u16, Value)>,
+) -> (String, mpsc::Receiver<String>, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind server");
+    let endpoint = format!("http://{}", listener.local_addr().expect("local addr"));
+    let (tx, rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        for ((status, body), stream) in responses.into_iter().zip(listener.incoming()) {
+            let mut stream = stream.expect("accept");
+            let (request_line, _, _) = read_http_request(&mut stream);
+            let body = body.to_string();
+            let response = format!(
+                "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let _ = tx.send(path.to_string());
+        }
+    });
+
+    (endpoint, rx, handle)
+}
+
 #[test]
 #[ignore]
 fn cli_e2e_flow() {
@@ -320,7 +408,7 @@ fn cli_e2e_flow() {
         .expect("publish output");
     assert!(publish_output.status.success());
 
-    let payload = rx
+    let (payload, _) = rx
         .recv_timeout(Duration::from_secs(5))
         .expect("publish payload");
     assert!(payload.get("manifest").is_some());
@@ -439,3 +527,2518 @@ fn cli_e2e_verify_mismatch() {
         .failure()
         .code(3);
 }
+
+#[test]
+#[ignore]
+fn cli_e2e_status_reports_proof_state() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "status");
+    let (proof_a, proof_b) = write_proof_files(&temp);
+
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace.to_str().expect("workspace str"),
+            "--proof",
+            proof_a.to_str().expect("proof a str"),
+            "--proof",
+            proof_b.to_str().expect("proof b str"),
+        ])
+        .assert()
+        .success();
+
+    fs::write(&proof_a, "alpha evidence, edited").expect("modify proof a");
+    fs::remove_file(&proof_b).expect("remove proof b");
+
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "status",
+            "--path",
+            workspace.to_str().expect("workspace str"),
+        ])
+        .output()
+        .expect("status output");
+    assert!(output.status.success());
+    let status: Value = serde_json::from_slice(&output.stdout).expect("status json");
+    let result = &status["result"];
+    assert_eq!(result["template"].as_str().expect("template"), "code");
+    assert_eq!(
+        result["title"].as_str().expect("title"),
+        "CLI E2E Disclosure"
+    );
+    assert_eq!(result["global"]["human_percent"].as_i64(), Some(70));
+    assert_eq!(result["stages"].as_array().expect("stages").len(), 5);
+    let states: Vec<&str> = result["proof"]
+        .as_array()
+        .expect("proof")
+        .iter()
+        .map(|item| item["state"].as_str().expect("state"))
+        .collect();
+    assert_eq!(states, vec!["modified", "missing"]);
+    assert!(result["hashes"]["up_to_date"]
+        .as_bool()
+        .expect("up to date"));
+    assert_eq!(result["receipt"]["status"].as_str(), Some("none"));
+    assert!(result.get("publication").is_none());
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_migrate_versions() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "migrate");
+
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "migrate",
+            "--path",
+            workspace.to_str().expect("workspace str"),
+        ])
+        .output()
+        .expect("migrate output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("migrate json");
+    assert_eq!(
+        report["result"]["from_version"],
+        report["result"]["to_version"]
+    );
+    assert!(report["result"]["applied"]
+        .as_array()
+        .expect("applied")
+        .is_empty());
+
+    let manifest_path = workspace.join("disclosure.json");
+    let mut manifest = read_json(&manifest_path);
+    manifest["version"] = Value::String("1.0.0".to_string());
+    for stage in manifest["assistance"]["stages"]
+        .as_array_mut()
+        .expect("stages")
+    {
+        stage
+            .as_object_mut()
+            .expect("stage")
+            .remove("ai_percent_range");
+    }
+    fs::write(&manifest_path, manifest.to_string()).expect("write manifest");
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "migrate",
+            "--path",
+            workspace.to_str().expect("workspace str"),
+        ])
+        .output()
+        .expect("migrate output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("migrate json");
+    assert_eq!(report["result"]["from_version"], "1.0.0");
+    assert_eq!(
+        report["result"]["applied"]
+            .as_array()
+            .expect("applied")
+            .len(),
+        1
+    );
+    let mut manifest = read_json(&manifest_path);
+    assert_eq!(
+        manifest["assistance"]["stages"][0]["ai_percent_range"]["max"],
+        0
+    );
+
+    manifest["version"] = Value::String("99.0.0".to_string());
+    fs::write(&manifest_path, manifest.to_string()).expect("write manifest");
+
+    disclose_cmd()
+        .args([
+            "status",
+            "--path",
+            workspace.to_str().expect("workspace str"),
+        ])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+/// Copies a workspace from `tests/fixtures` into `temp`.
+fn copy_fixture(temp: &TempDir, name: &str) -> PathBuf {
+    fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+        fs::create_dir_all(to).expect("fixture dir");
+        for entry in fs::read_dir(from).expect("read fixture") {
+            let entry = entry.expect("fixture entry");
+            let target = to.join(entry.file_name());
+            if entry.file_type().expect("file type").is_dir() {
+                copy_dir(&entry.path(), &target);
+            } else {
+                fs::copy(entry.path(), target).expect("copy fixture");
+            }
+        }
+    }
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    let workspace = temp.path().join(name);
+    copy_dir(&source, &workspace);
+    workspace
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_legacy_manifest_needs_migrate() {
+    // Written by the 1.0.0 CLI: init, attach proof/notes.txt, meter 70/30.
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = copy_fixture(&temp, "legacy-1.0.0");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let stored_root = read_json(&workspace.join("hashes.json"))["bundle_root_sha256"].clone();
+
+    let output = disclose_cmd()
+        .args(["--json", "status", "--path", workspace_str])
+        .output()
+        .expect("status output");
+    assert!(output.status.success(), "{:?}", output);
+    let status: Value = serde_json::from_slice(&output.stdout).expect("status json");
+    let result = &status["result"];
+    assert_eq!(result["version"], "1.0.0");
+    assert_eq!(result["migrates_to"], "1.1.0");
+    assert_eq!(result["hashes"]["up_to_date"], true);
+    assert_eq!(result["hashes"]["bundle_root_sha256"], stored_root);
+
+    let out = temp.path().join("legacy-export");
+    let refused = [
+        vec![
+            "export",
+            "--format",
+            "dir",
+            "--bundle",
+            out.to_str().expect("out str"),
+        ],
+        vec!["publish", "--dry-run", "--endpoint", "http://127.0.0.1:9"],
+        vec!["stamp", "--ots", "--timeout", "10"],
+    ];
+    for args in &refused {
+        let output = disclose_cmd()
+            .args(args)
+            .args(["--path", workspace_str])
+            .output()
+            .expect("refused output");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("disclose migrate"));
+    }
+    assert!(!out.exists());
+    assert!(!workspace.join("receipts").exists());
+
+    disclose_cmd()
+        .args(["migrate", "--path", workspace_str])
+        .assert()
+        .success();
+    // Stamping after the migration is covered by cli_e2e_flow.
+    for args in &refused[..2] {
+        disclose_cmd()
+            .args(args)
+            .args(["--path", workspace_str])
+            .assert()
+            .success();
+    }
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    let hashes = read_json(&workspace.join("hashes.json"));
+    assert_eq!(manifest["version"], "1.1.0");
+    assert_eq!(
+        manifest["proof"]["bundle_root_sha256"],
+        hashes["bundle_root_sha256"]
+    );
+    let exported = read_json(&out.join("hashes.json"));
+    assert_eq!(exported["bundle_root_sha256"], hashes["bundle_root_sha256"]);
+}
+
+fn write_template(path: &std::path::Path, slug: &str, version: &str, stages: &[&str]) {
+    let stages: Vec<Value> = stages
+        .iter()
+        .map(|key| serde_json::json!({ "key": key, "label": key.to_uppercase() }))
+        .collect();
+    let template = serde_json::json!({
+        "slug": slug,
+        "version": version,
+        "label": format!("{} template", slug),
+        "recommended_proof": ["Raw recording"],
+        "stages": stages
+    });
+    fs::create_dir_all(path.parent().expect("template parent")).expect("template dir");
+    fs::write(path, template.to_string()).expect("write template");
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_custom_templates() {
+    let temp = TempDir::new().expect("tempdir");
+    let config_dir = temp.path().join("config");
+    write_template(
+        &config_dir.join("templates").join("podcast.json"),
+        "podcast",
+        "1.0.0",
+        &["research", "recording", "editing"],
+    );
+
+    let podcast = temp.path().join("podcast");
+    disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args([
+            "init",
+            "--template",
+            "podcast",
+            "--title",
+            "Episode 1",
+            "--out",
+            podcast.to_str().expect("podcast str"),
+        ])
+        .assert()
+        .success();
+    let manifest = read_json(&podcast.join("disclosure.json"));
+    assert_eq!(manifest["template"]["slug"], "podcast");
+    assert_eq!(manifest["assistance"]["stages"][1]["key"], "recording");
+
+    let template_file = temp.path().join("paper.json");
+    write_template(
+        &template_file,
+        "paper",
+        "2.1.0",
+        &["literature", "analysis"],
+    );
+    let paper = temp.path().join("paper");
+    disclose_cmd()
+        .args([
+            "init",
+            "--template-file",
+            template_file.to_str().expect("template str"),
+            "--title",
+            "Paper",
+            "--out",
+            paper.to_str().expect("paper str"),
+        ])
+        .assert()
+        .success();
+    assert!(paper.join("templates").join("paper.json").exists());
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            paper.to_str().expect("paper str"),
+            "--stage",
+            "analysis=heavy",
+        ])
+        .assert()
+        .success();
+
+    // Init into an existing folder resolves its templates/ like later commands.
+    let zine = temp.path().join("zine");
+    write_template(
+        &zine.join("templates").join("zine.json"),
+        "zine",
+        "1.0.0",
+        &["layout", "printing"],
+    );
+    disclose_cmd()
+        .args(["init", "--template", "zine", "--title", "Zine", "--out"])
+        .arg(&zine)
+        .assert()
+        .success();
+    let manifest = read_json(&zine.join("disclosure.json"));
+    assert_eq!(manifest["assistance"]["stages"][0]["key"], "layout");
+
+    // A changed copy of a built-in must bump its version.
+    let shadow = temp.path().join("code.json");
+    write_template(&shadow, "code", "1.0.0", &["hacking"]);
+    let output = disclose_cmd()
+        .args(["init", "--title", "Shadow", "--template-file"])
+        .arg(&shadow)
+        .arg("--out")
+        .arg(temp.path().join("shadow"))
+        .output()
+        .expect("init output");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Bump its version"));
+
+    let invalid = temp.path().join("invalid.json");
+    fs::write(
+        &invalid,
+        r#"{ "slug": "Bad Slug", "version": "1", "stages": [] }"#,
+    )
+    .expect("write invalid");
+    disclose_cmd()
+        .args([
+            "init",
+            "--template-file",
+            invalid.to_str().expect("invalid str"),
+            "--title",
+            "Invalid",
+            "--out",
+            temp.path().join("invalid").to_str().expect("out str"),
+        ])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_templates_commands() {
+    let temp = TempDir::new().expect("tempdir");
+    let config_dir = temp.path().join("config");
+    write_template(
+        &config_dir.join("templates").join("design.json"),
+        "design-assets",
+        "1.0.0",
+        &["moodboard", "mockups"],
+    );
+
+    let output = disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args(["--json", "templates", "list"])
+        .output()
+        .expect("list output");
+    assert!(output.status.success());
+    let listing: Value = serde_json::from_slice(&output.stdout).expect("list json");
+    let slugs: Vec<&str> = listing["result"]["templates"]
+        .as_array()
+        .expect("templates")
+        .iter()
+        .map(|template| template["slug"].as_str().expect("slug"))
+        .collect();
+    assert!(slugs.contains(&"article"));
+    assert!(slugs.contains(&"code"));
+    assert!(slugs.contains(&"design-assets"));
+
+    let output = disclose_cmd()
+        .args(["--json", "templates", "show", "code"])
+        .output()
+        .expect("show output");
+    assert!(output.status.success());
+    let shown: Value = serde_json::from_slice(&output.stdout).expect("show json");
+    assert_eq!(
+        shown["result"]["stages"].as_array().expect("stages").len(),
+        5
+    );
+    assert_eq!(shown["result"]["source"]["kind"], "builtin");
+
+    let output = disclose_cmd()
+        .args(["templates", "show", "artcle"])
+        .output()
+        .expect("unknown output");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("article"));
+
+    let candidate = temp.path().join("candidate.json");
+    write_template(&candidate, "code", "1.0.0", &["design", "design"]);
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "templates",
+            "validate",
+            candidate.to_str().expect("candidate str"),
+        ])
+        .output()
+        .expect("validate output");
+    assert_eq!(output.status.code(), Some(2));
+    let lint: Value = serde_json::from_slice(&output.stdout).expect("lint json");
+    assert!(!lint["result"]["valid"].as_bool().expect("valid"));
+
+    write_template(&candidate, "code", "1.0.0", &["design", "build"]);
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "templates",
+            "validate",
+            candidate.to_str().expect("candidate str"),
+        ])
+        .output()
+        .expect("validate output");
+    assert!(output.status.success());
+    let lint: Value = serde_json::from_slice(&output.stdout).expect("lint json");
+    assert!(lint["result"]["valid"].as_bool().expect("valid"));
+    assert_eq!(
+        lint["result"]["warnings"]
+            .as_array()
+            .expect("warnings")
+            .len(),
+        1
+    );
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_template_upgrade() {
+    let temp = TempDir::new().expect("tempdir");
+    let config_dir = temp.path().join("config");
+    let template_path = config_dir.join("templates").join("podcast.json");
+    write_template(
+        &template_path,
+        "podcast",
+        "1.0.0",
+        &["research", "recording", "editing"],
+    );
+
+    let podcast = temp.path().join("podcast");
+    let podcast_str = podcast.to_str().expect("podcast str");
+    disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args([
+            "init",
+            "--template",
+            "podcast",
+            "--title",
+            "Episode 1",
+            "--out",
+            podcast_str,
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args(["meter", "--path", podcast_str, "--stage", "recording=heavy"])
+        .assert()
+        .success();
+    disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args([
+            "tools",
+            "add",
+            "--path",
+            podcast_str,
+            "--name",
+            "Descript",
+            "--stage",
+            "recording",
+            "--stage",
+            "editing",
+        ])
+        .assert()
+        .success();
+
+    write_template(
+        &template_path,
+        "podcast",
+        "2.0.0",
+        &["research", "production", "publishing"],
+    );
+    disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args(["meter", "--path", podcast_str, "--stage", "research=light"])
+        .assert()
+        .failure()
+        .code(2);
+
+    let output = disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args([
+            "--json",
+            "template",
+            "upgrade",
+            "--path",
+            podcast_str,
+            "--map",
+            "recording=production",
+        ])
+        .output()
+        .expect("upgrade output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("upgrade json");
+    assert_eq!(report["result"]["from_version"], "1.0.0");
+    assert_eq!(report["result"]["to_version"], "2.0.0");
+    assert_eq!(report["result"]["added"][0], "publishing");
+    assert_eq!(report["result"]["removed"][0], "editing (none)");
+    assert_eq!(
+        report["result"]["dropped"],
+        serde_json::json!(["Descript: editing"])
+    );
+
+    let manifest = read_json(&podcast.join("disclosure.json"));
+    assert_eq!(manifest["template"]["version"], "2.0.0");
+    let stages = manifest["assistance"]["stages"].as_array().expect("stages");
+    let keys: Vec<&str> = stages
+        .iter()
+        .map(|stage| stage["key"].as_str().expect("key"))
+        .collect();
+    assert_eq!(keys, vec!["research", "production", "publishing"]);
+    assert_eq!(stages[1]["grade"], "heavy");
+    assert_eq!(
+        manifest["ai_tools"][0]["stages"],
+        serde_json::json!(["production"])
+    );
+
+    disclose_cmd()
+        .env("DISCLOSE_CONFIG_DIR", &config_dir)
+        .args([
+            "meter",
+            "--path",
+            podcast_str,
+            "--stage",
+            "publishing=light",
+        ])
+        .assert()
+        .success();
+
+    // Going back to 1.0.0 needs an explicit flag.
+    write_template(
+        &config_dir.join("templates").join("podcast-v1.json"),
+        "podcast",
+        "1.0.0",
+        &["research", "recording", "editing"],
+    );
+    let downgrade = |extra: &[&str]| {
+        disclose_cmd()
+            .env("DISCLOSE_CONFIG_DIR", &config_dir)
+            .args([
+                "template",
+                "upgrade",
+                "--path",
+                podcast_str,
+                "--to",
+                "1.0.0",
+            ])
+            .args(extra)
+            .output()
+            .expect("downgrade output")
+    };
+    let output = downgrade(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--allow-downgrade"));
+    let manifest = read_json(&podcast.join("disclosure.json"));
+    assert_eq!(manifest["template"]["version"], "2.0.0");
+    let output = downgrade(&["--allow-downgrade"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Downgraded template podcast from 2.0.0 to 1.0.0"));
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_builtin_template_archive() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "archived");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--stage",
+            "implementation=heavy",
+        ])
+        .assert()
+        .success();
+
+    // code 0.9.0 only ships in shared/templates/archive/.
+    disclose_cmd()
+        .args([
+            "template",
+            "upgrade",
+            "--path",
+            workspace_str,
+            "--to",
+            "0.9.0",
+            "--allow-downgrade",
+            "--map",
+            "implementation=coding",
+        ])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["template"]["version"], "0.9.0");
+    let keys: Vec<&str> = manifest["assistance"]["stages"]
+        .as_array()
+        .expect("stages")
+        .iter()
+        .map(|stage| stage["key"].as_str().expect("key"))
+        .collect();
+    assert_eq!(keys, vec!["design", "coding", "testing", "docs"]);
+
+    // The pinned workspace keeps resolving the archived version.
+    disclose_cmd()
+        .args(["meter", "--path", workspace_str, "--stage", "coding=light"])
+        .assert()
+        .success();
+
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "template",
+            "upgrade",
+            "--path",
+            workspace_str,
+            "--map",
+            "coding=implementation",
+        ])
+        .output()
+        .expect("upgrade output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("upgrade json");
+    assert_eq!(report["result"]["to_version"], "1.0.0");
+    assert_eq!(report["result"]["added"], serde_json::json!(["review"]));
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["assistance"]["stages"][1]["key"], "implementation");
+    assert_eq!(manifest["assistance"]["stages"][1]["grade"], "light");
+}
+#[test]
+#[ignore]
+fn cli_e2e_proof_completeness() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "completeness");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let (notes, design) = write_proof_files(&temp);
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["proof"]["completeness"]["score_percent"], 0);
+
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace_str,
+            "--proof",
+            design.to_str().expect("design str"),
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace_str,
+            "--git",
+            "https://github.com/example/repo",
+            "--commit",
+            "abc123",
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace_str,
+            "--proof",
+            notes.to_str().expect("notes str"),
+            "--slot",
+            "issue-lst",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+
+    let output = disclose_cmd()
+        .args(["--json", "status", "--path", workspace_str])
+        .output()
+        .expect("status output");
+    let status: Value = serde_json::from_slice(&output.stdout).expect("status json");
+    assert_eq!(status["result"]["completeness"]["score_percent"], 67);
+
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace_str,
+            "--proof",
+            notes.to_str().expect("notes str"),
+            "--slot",
+            "Issue list",
+        ])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    let items = manifest["proof"]["items"].as_array().expect("items");
+    assert_eq!(items[2]["slot"], "issue-list");
+    let completeness = &manifest["proof"]["completeness"];
+    assert_eq!(completeness["score_percent"], 100);
+    let slots = completeness["slots"].as_array().expect("slots");
+    assert_eq!(slots[0]["key"], "pre-ai-commit-hash");
+    assert_eq!(slots[0]["proof_ids"][0], items[1]["id"]);
+    assert_eq!(slots[1]["proof_ids"][0], items[0]["id"]);
+    assert_eq!(slots[3]["optional"], true);
+
+    let output = disclose_cmd()
+        .args([
+            "export",
+            "--path",
+            workspace_str,
+            "--bundle",
+            temp.path().join("bundle.zip").to_str().expect("bundle str"),
+        ])
+        .output()
+        .expect("export output");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[ ] Repo snapshot zip (optional)"));
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_custom_grade_scale() {
+    let temp = TempDir::new().expect("tempdir");
+    let template_file = temp.path().join("review.json");
+    let template = serde_json::json!({
+        "slug": "review",
+        "version": "1.0.0",
+        "label": "Peer review",
+        "stages": [{ "key": "reading", "label": "Reading" }, { "key": "writing", "label": "Writing" }],
+        "grades": [
+            { "key": "low", "label": "Low", "min_percent": 0, "max_percent": 20 },
+            { "key": "medium", "label": "Medium", "min_percent": 21, "max_percent": 60, "percent": 40 },
+            { "key": "high", "label": "High", "min_percent": 61, "max_percent": 100 }
+        ]
+    });
+    fs::write(&template_file, template.to_string()).expect("write template");
+
+    let workspace = temp.path().join("review");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    disclose_cmd()
+        .args([
+            "init",
+            "--template-file",
+            template_file.to_str().expect("template str"),
+            "--title",
+            "Review",
+            "--out",
+            workspace_str,
+        ])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["assistance"]["stages"][0]["grade"], "low");
+    assert_eq!(manifest["assistance"]["stages"][0]["approx_ai_percent"], 10);
+
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--stage",
+            "writing=high",
+            "--stage",
+            "reading=medium",
+        ])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    let stages = manifest["assistance"]["stages"].as_array().expect("stages");
+    assert_eq!(stages[0]["key"], "reading");
+    assert_eq!(stages[0]["approx_ai_percent"], 40);
+    assert_eq!(stages[1]["grade"], "high");
+    assert_eq!(stages[1]["approx_ai_percent"], 80);
+    assert_eq!(
+        stages[1]["ai_percent_range"],
+        serde_json::json!({ "min": 61, "max": 100 })
+    );
+
+    let output = disclose_cmd()
+        .args(["meter", "--path", workspace_str, "--stage", "writing=heavy"])
+        .output()
+        .expect("meter output");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("low, medium, high"));
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_meter_split_check() {
+    let temp = TempDir::new().expect("tempdir");
+    let template_file = temp.path().join("essay.json");
+    write_template(&template_file, "essay", "1.0.0", &["drafting", "editing"]);
+    let mut template = read_json(&template_file);
+    template["stages"][0]["weight"] = serde_json::json!(3);
+    fs::write(&template_file, template.to_string()).expect("write template");
+
+    let workspace = temp.path().join("essay");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    disclose_cmd()
+        .args([
+            "init",
+            "--template-file",
+            template_file.to_str().expect("template str"),
+            "--title",
+            "Essay",
+            "--out",
+            workspace_str,
+        ])
+        .assert()
+        .success();
+
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "meter",
+            "--path",
+            workspace_str,
+            "--stage",
+            "drafting=heavy",
+        ])
+        .output()
+        .expect("meter output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("meter json");
+    assert_eq!(report["result"]["split"]["derived_ai_percent"], 45);
+    assert_eq!(report["result"]["split"]["within_tolerance"], true);
+
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--global-human",
+            "90",
+            "--split-mode",
+            "reject",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+    assert_eq!(
+        read_json(&workspace.join("disclosure.json"))["assistance"]["global"]["ai_percent"],
+        30
+    );
+
+    let output = disclose_cmd()
+        .args(["meter", "--path", workspace_str, "--global-human", "90"])
+        .output()
+        .expect("meter output");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("~45% AI"));
+
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--global-human",
+            "50",
+            "--split-mode",
+            "derive",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+    disclose_cmd()
+        .args(["meter", "--path", workspace_str, "--split-mode", "derive"])
+        .assert()
+        .success();
+    let global = read_json(&workspace.join("disclosure.json"))["assistance"]["global"].clone();
+    assert_eq!(global["human_percent"], 55);
+    assert_eq!(global["ai_percent"], 45);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_scoped_assistance() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "scoped");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let (notes, design) = write_proof_files(&temp);
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace_str,
+            "--proof",
+            notes.to_str().expect("notes str"),
+            "--proof",
+            design.to_str().expect("design str"),
+        ])
+        .assert()
+        .success();
+    let before = read_json(&workspace.join("hashes.json"))["bundle_root_sha256"].clone();
+
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--scope",
+            "glob:**/*.md",
+            "--scope-ai",
+            "80",
+            "--scope-note",
+            "Drafted with an assistant",
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--scope",
+            "proof:notes.txt",
+            "--scope-grade",
+            "light",
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--scope",
+            "component:core",
+            "--scope-human",
+            "90",
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--scope",
+            "component:core",
+            "--scope-grade",
+            "heavy",
+            "--scope-ai",
+            "10",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    let scoped = manifest["assistance"]["scoped"].as_array().expect("scoped");
+    assert_eq!(scoped.len(), 3);
+    assert_eq!(scoped[0]["scope"]["kind"], "glob");
+    assert_eq!(scoped[0]["human_percent"], 20);
+    assert_eq!(
+        scoped[1]["scope"]["value"],
+        manifest["proof"]["items"][0]["id"]
+    );
+    assert_eq!(scoped[1]["ai_percent"], 10);
+    assert_eq!(scoped[1]["ai_percent_range"]["max"], 20);
+    assert_eq!(scoped[2]["ai_percent"], 10);
+    let after = read_json(&workspace.join("hashes.json"))["bundle_root_sha256"].clone();
+    assert_ne!(before, after);
+
+    let output = disclose_cmd()
+        .args(["--json", "status", "--path", workspace_str])
+        .output()
+        .expect("status output");
+    let status: Value = serde_json::from_slice(&output.stdout).expect("status json");
+    let scoped = status["result"]["scoped"].as_array().expect("scoped");
+    assert_eq!(
+        scoped[0]["proof_ids"][0],
+        manifest["proof"]["items"][1]["id"]
+    );
+
+    // A rejected stage or split leaves the scope change unwritten too.
+    let hashes_before = fs::read(workspace.join("hashes.json")).expect("hashes");
+    for extra in [
+        ["--stage", "implementation=extreme"],
+        ["--global-human", "50"],
+    ] {
+        disclose_cmd()
+            .args(["meter", "--path", workspace_str])
+            .args(["--remove-scope", "component:core", "--split-mode", "derive"])
+            .args(extra)
+            .assert()
+            .failure()
+            .code(2);
+    }
+    assert_eq!(
+        read_json(&workspace.join("disclosure.json"))["assistance"]["scoped"],
+        manifest["assistance"]["scoped"]
+    );
+    assert_eq!(
+        fs::read(workspace.join("hashes.json")).expect("hashes"),
+        hashes_before
+    );
+
+    disclose_cmd()
+        .args([
+            "meter",
+            "--path",
+            workspace_str,
+            "--remove-scope",
+            "component:core",
+        ])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(
+        manifest["assistance"]["scoped"]
+            .as_array()
+            .expect("scoped")
+            .len(),
+        2
+    );
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_tools_commands() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "tools");
+    let workspace_str = workspace.to_str().expect("workspace str");
+
+    disclose_cmd()
+        .args([
+            "tools",
+            "add",
+            "--path",
+            workspace_str,
+            "--name",
+            "Copilot",
+            "--provider",
+            "GitHub",
+            "--model",
+            "gpt-4o",
+            "--version",
+            "1.2",
+            "--stage",
+            "implementation",
+            "--stage",
+            "testing",
+        ])
+        .assert()
+        .success();
+    disclose_cmd()
+        .args(["tools", "add", "--path", workspace_str, "--name", "copilot"])
+        .assert()
+        .failure()
+        .code(2);
+    disclose_cmd()
+        .args([
+            "tools",
+            "add",
+            "--path",
+            workspace_str,
+            "--name",
+            "Aider",
+            "--stage",
+            "drafting",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    let tool = &manifest["ai_tools"][0];
+    assert_eq!(tool["version"], "1.2");
+    assert_eq!(tool["stages"][1], "testing");
+    let hashes = read_json(&workspace.join("hashes.json"));
+    assert_eq!(
+        manifest["proof"]["bundle_root_sha256"],
+        hashes["bundle_root_sha256"]
+    );
+
+    let output = disclose_cmd()
+        .args(["--json", "tools", "list", "--path", workspace_str])
+        .output()
+        .expect("list output");
+    let listing: Value = serde_json::from_slice(&output.stdout).expect("list json");
+    assert_eq!(listing["result"]["tools"][0]["name"], "Copilot");
+
+    disclose_cmd()
+        .args(["tools", "remove", "copilot", "--path", workspace_str])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert!(manifest.get("ai_tools").is_none());
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_import_usage() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "usage");
+    let workspace_str = workspace.to_str().expect("workspace str");
+
+    let aider_log = temp.path().join(".aider.chat.history.md");
+    fs::write(
+        &aider_log,
+        "# aider chat started at 2025-02-01 09:30:00\n\n> Aider v0.50.1\n> Main model: claude-3-5-sonnet-20240620 with diff edit format\n\n#### SECRET-PROMPT please add testing for the parser\n\nDone.\n",
+    )
+    .expect("write aider log");
+    let export = temp.path().join("chat.jsonl");
+    fs::write(
+        &export,
+        [
+            r#"{"conversation_id":"a","role":"user","content":"SECRET-PROMPT review the design","created_at":"2025-02-03T10:00:00Z"}"#,
+            r#"{"conversation_id":"a","role":"assistant","model":"gpt-4o","content":"SECRET-ANSWER"}"#,
+        ]
+        .join("\n"),
+    )
+    .expect("write export");
+    let before = fs::read_to_string(workspace.join("disclosure.json")).expect("manifest");
+
+    let output = disclose_cmd()
+        .args(["import-usage", "--path", workspace_str])
+        .arg(&aider_log)
+        .arg(&export)
+        .output()
+        .expect("import text output");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Re-run with --apply"));
+
+    let output = disclose_cmd()
+        .args(["--json", "import-usage", "--path", workspace_str])
+        .arg(&aider_log)
+        .arg(&export)
+        .output()
+        .expect("import output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("import json");
+    let result = &report["result"];
+    assert_eq!(result["summary"]["sessions"], 2);
+    assert_eq!(result["summary"]["tools"][0]["name"], "aider");
+    assert_eq!(result["proposed_tools"][1]["provider"], "OpenAI");
+    // Each tool is linked to the stages of its own sessions only.
+    assert_eq!(
+        result["proposed_tools"][0]["stages"],
+        serde_json::json!(["testing"])
+    );
+    let chat_stages = result["proposed_tools"][1]["stages"]
+        .as_array()
+        .expect("chat stages");
+    assert!(chat_stages.contains(&Value::from("design")));
+    assert!(!chat_stages.contains(&Value::from("testing")));
+    assert_eq!(result["applied"], false);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("SECRET"));
+    let after = fs::read_to_string(workspace.join("disclosure.json")).expect("manifest");
+    assert_eq!(before, after);
+
+    disclose_cmd()
+        .args(["import-usage", "--apply", "--path", workspace_str])
+        .arg(&aider_log)
+        .arg(&export)
+        .assert()
+        .success();
+    let raw = fs::read_to_string(workspace.join("disclosure.json")).expect("manifest");
+    assert!(!raw.contains("SECRET"));
+    let manifest: Value = serde_json::from_str(&raw).expect("manifest json");
+    assert_eq!(manifest["ai_tools"][0]["version"], "0.50.1");
+    assert_eq!(manifest["ai_tools"][1]["name"], "ChatGPT");
+    let stages = manifest["assistance"]["stages"].as_array().expect("stages");
+    let testing = stages
+        .iter()
+        .find(|stage| stage["key"] == "testing")
+        .expect("testing stage");
+    assert_ne!(testing["grade"], "none");
+
+    disclose_cmd()
+        .args(["import-usage", "--path", workspace_str])
+        .arg(temp.path().join("missing.txt"))
+        .assert()
+        .failure();
+}
+
+fn git(repo: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=Ada", "-c", "user.email=ada@example.com"])
+        .args(args)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_meter_from_git() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "from-git");
+    let workspace_str = workspace.to_str().expect("workspace str");
+
+    let repo = temp.path().join("repo");
+    fs::create_dir_all(repo.join("src")).expect("src dir");
+    fs::create_dir_all(repo.join("tests")).expect("tests dir");
+    git(&repo, &["init", "-q"]);
+    fs::write(repo.join("src/lib.rs"), "fn a() {}\nfn b() {}\nfn c() {}\n").expect("lib");
+    fs::write(repo.join("README.md"), "# Demo\n").expect("readme");
+    git(&repo, &["add", "-A"]);
+    git(&repo, &["commit", "-q", "-m", "Initial implementation"]);
+    fs::write(repo.join("tests/lib.rs"), "#[test]\nfn a() {}\n").expect("test");
+    git(&repo, &["add", "-A"]);
+    git(
+        &repo,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            "Add tests\n\nCo-authored-by: Claude <noreply@anthropic.com>",
+        ],
+    );
+
+    fs::write(workspace.join("disclosure.json"), {
+        let mut manifest = read_json(&workspace.join("disclosure.json"));
+        manifest["assistance"]["notes"] = Value::from("Reviewed by hand.");
+        serde_json::to_string_pretty(&manifest).expect("manifest")
+    })
+    .expect("write manifest");
+
+    // Without --apply the analysis is only proposed.
+    let unchanged = fs::read(workspace.join("disclosure.json")).expect("manifest");
+    let output = disclose_cmd()
+        .args(["--json", "meter", "--path", workspace_str, "--from-git"])
+        .arg(&repo)
+        .args(["--stage", "docs=light"])
+        .output()
+        .expect("meter output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("meter json");
+    assert_eq!(report["result"]["applied"], false);
+    assert_eq!(report["result"]["proposed"]["ai_percent"], 33);
+    let analysis = &report["result"]["git"];
+    assert_eq!(analysis["commits"], 2);
+    assert_eq!(analysis["ai_commits"], 1);
+    assert_eq!(analysis["ai_co_authors"][0], "Claude");
+    assert_eq!(
+        fs::read(workspace.join("disclosure.json")).expect("manifest"),
+        unchanged
+    );
+    let output = disclose_cmd()
+        .args(["meter", "--path", workspace_str, "--from-git"])
+        .arg(&repo)
+        .output()
+        .expect("meter output");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Re-run with --apply"));
+
+    disclose_cmd()
+        .args(["meter", "--path", workspace_str, "--apply", "--from-git"])
+        .arg(&repo)
+        .args(["--stage", "docs=light"])
+        .assert()
+        .success();
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["assistance"]["global"]["ai_percent"], 33);
+    let stages = manifest["assistance"]["stages"].as_array().expect("stages");
+    let grade = |key: &str| {
+        stages
+            .iter()
+            .find(|stage| stage["key"] == key)
+            .map(|stage| stage["grade"].clone())
+    };
+    assert_eq!(grade("testing"), Some(Value::from("full")));
+    assert_eq!(grade("implementation"), Some(Value::from("none")));
+    assert_eq!(grade("docs"), Some(Value::from("light")));
+    let notes = manifest["assistance"]["notes"].as_str().expect("notes");
+    assert!(notes.starts_with("Reviewed by hand.\n\nFrom git history: 1 of 2 commits"));
+
+    disclose_cmd()
+        .args(["--yes", "meter", "--path", workspace_str, "--from-git"])
+        .arg(&repo)
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    let notes = manifest["assistance"]["notes"].as_str().expect("notes");
+    assert_eq!(notes.matches("From git history:").count(), 1);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_project_set() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "project");
+    let workspace_str = workspace.to_str().expect("workspace str");
+
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "project",
+            "set",
+            "--path",
+            workspace_str,
+            "--title",
+            "Renamed",
+            "--author",
+            "Ada",
+            "--add-link",
+            "https://example.com/a",
+            "--add-link",
+            "https://example.com/b",
+            "--audience",
+            "school",
+            "--assistance-notes",
+            "Drafted by hand, polished with help.",
+        ])
+        .output()
+        .expect("project output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("project json");
+    assert_eq!(report["result"]["receipt_invalidated"], false);
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["project"]["title"], "Renamed");
+    assert_eq!(manifest["project"]["audience"], "school");
+    assert_eq!(manifest["project"]["links"][1], "https://example.com/b");
+    assert_eq!(
+        manifest["assistance"]["notes"],
+        "Drafted by hand, polished with help."
+    );
+    let hashes = read_json(&workspace.join("hashes.json"));
+    assert_eq!(
+        manifest["proof"]["bundle_root_sha256"],
+        hashes["bundle_root_sha256"]
+    );
+
+    for args in [
+        vec!["--audience", "everyone"],
+        vec!["--remove-link", "https://example.com/missing"],
+        vec!["--title", " "],
+        vec![],
+    ] {
+        disclose_cmd()
+            .args(["project", "set", "--path", workspace_str])
+            .args(args)
+            .assert()
+            .failure()
+            .code(2);
+    }
+
+    let mut stamped = read_json(&workspace.join("disclosure.json"));
+    stamped["timestamps"] = serde_json::json!({
+        "opentimestamps": { "status": "pending", "receipt_sha256": "0".repeat(64) }
+    });
+    fs::write(
+        workspace.join("disclosure.json"),
+        serde_json::to_string_pretty(&stamped).expect("manifest"),
+    )
+    .expect("write manifest");
+    let output = disclose_cmd()
+        .args([
+            "project",
+            "set",
+            "--path",
+            workspace_str,
+            "--remove-link",
+            "https://example.com/a",
+            "--author",
+            "",
+        ])
+        .output()
+        .expect("project output");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("receipt no longer matches"));
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert!(manifest["project"].get("author").is_none());
+    assert_eq!(
+        manifest["project"]["links"].as_array().map(Vec::len),
+        Some(1)
+    );
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_init_existing_dir() {
+    let temp = TempDir::new().expect("tempdir");
+    let project = temp.path().join("project");
+    fs::create_dir_all(&project).expect("project dir");
+    fs::write(project.join("README.md"), "# Project\n").expect("readme");
+    let project_str = project.to_str().expect("project str");
+    let init = |extra: &[&str]| {
+        let mut cmd = disclose_cmd();
+        cmd.args(["init", "--out", project_str]).args(extra);
+        cmd
+    };
+
+    init(&["--template", "code", "--title", "In place"])
+        .assert()
+        .success();
+    let first = read_json(&project.join("disclosure.json"));
+    assert!(project.join("README.md").is_file());
+
+    init(&["--template", "code", "--title", "Again"])
+        .assert()
+        .failure()
+        .code(2);
+
+    fs::create_dir_all(project.join("receipts")).expect("receipts dir");
+    fs::write(project.join("receipts/bundle-root.ots"), "old receipt").expect("receipt");
+    let output = init(&["--template", "code", "--title", "Fresh", "--force"])
+        .arg("--json")
+        .output()
+        .expect("init output");
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("init json");
+    let backup = PathBuf::from(report["result"]["backup"].as_str().expect("backup"));
+    let saved = read_json(&backup.join("disclosure.json"));
+    assert_eq!(saved["id"], first["id"]);
+    assert!(backup.join("receipts/bundle-root.ots").is_file());
+    assert!(!project.join("receipts").exists());
+    let fresh = read_json(&project.join("disclosure.json"));
+    assert_eq!(fresh["project"]["title"], "Fresh");
+    assert_ne!(fresh["id"], first["id"]);
+
+    let example = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../shared/examples/disclosure.example.json");
+    let imported = temp.path().join("imported");
+    disclose_cmd()
+        .args(["init", "--out", imported.to_str().expect("imported str")])
+        .arg("--from")
+        .arg(&example)
+        .assert()
+        .success();
+    let manifest = read_json(&imported.join("disclosure.json"));
+    assert_eq!(manifest["template"]["slug"], "article");
+    assert_eq!(manifest["project"]["title"], "How I Built Disclose");
+    assert_eq!(manifest["ai_tools"].as_array().map(Vec::len), Some(2));
+    assert_eq!(
+        manifest["assistance"]["notes"],
+        "All final claims were verified manually."
+    );
+    let drafting = manifest["assistance"]["stages"]
+        .as_array()
+        .expect("stages")
+        .iter()
+        .find(|stage| stage["key"] == "drafting")
+        .cloned()
+        .expect("drafting");
+    assert_eq!(drafting["grade"], "moderate");
+    assert_eq!(manifest["proof"]["items"].as_array().map(Vec::len), Some(0));
+    assert_ne!(manifest["id"], "dsc_7e14a8d8-2f74-4f5a-9e16-5bdb5c6c8b7f");
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_workspace_discovery() {
+    let temp = TempDir::new().expect("tempdir");
+    let repo = temp.path().join("repo");
+    let nested = repo.join("src").join("deep");
+    fs::create_dir_all(&nested).expect("nested dir");
+
+    disclose_cmd()
+        .current_dir(&repo)
+        .args(["init", "--template", "code", "--title", "Default"])
+        .assert()
+        .success();
+    let output = disclose_cmd()
+        .current_dir(&nested)
+        .args(["--json", "status"])
+        .output()
+        .expect("status output");
+    assert!(output.status.success());
+    let status: Value = serde_json::from_slice(&output.stdout).expect("status json");
+    assert!(status["path"]
+        .as_str()
+        .expect("path")
+        .ends_with("repo/disclose"));
+
+    for (name, template) in [("paper", "article"), ("tool", "code")] {
+        disclose_cmd()
+            .current_dir(&repo)
+            .args(["--disclosure", name, "init", "--template", template])
+            .args(["--title", name, "--out"])
+            .arg(format!("disclosures/{}", name))
+            .assert()
+            .success();
+    }
+    let registry = read_json(&repo.join(".disclose").join("workspaces.json"));
+    assert_eq!(registry["workspaces"]["paper"], "disclosures/paper");
+
+    disclose_cmd()
+        .current_dir(&nested)
+        .arg("status")
+        .assert()
+        .failure()
+        .code(2);
+    let output = disclose_cmd()
+        .current_dir(&nested)
+        .args(["--json", "--disclosure", "paper", "status"])
+        .output()
+        .expect("status output");
+    assert!(output.status.success());
+    let status: Value = serde_json::from_slice(&output.stdout).expect("status json");
+    assert_eq!(status["result"]["template"], "article");
+    disclose_cmd()
+        .current_dir(&nested)
+        .args(["--disclosure", "missing", "status"])
+        .assert()
+        .failure()
+        .code(2);
+
+    // An explicit --path is the folder itself, never one of its parents.
+    disclose_cmd()
+        .args(["--disclosure", "paper", "status", "--path"])
+        .arg(&repo)
+        .assert()
+        .success();
+    disclose_cmd()
+        .args(["status", "--path"])
+        .arg(&nested)
+        .assert()
+        .failure()
+        .code(1);
+    let output = disclose_cmd()
+        .args(["status", "--path"])
+        .arg(repo.join("does-not-exist"))
+        .output()
+        .expect("status output");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not exist"));
+
+    fs::remove_dir_all(repo.join("disclosures").join("tool")).expect("remove tool");
+    let output = disclose_cmd()
+        .current_dir(&nested)
+        .args(["--disclosure", "tool", "status"])
+        .output()
+        .expect("status output");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("registered at"));
+
+    let outside = temp.path().join("outside");
+    fs::create_dir_all(&outside).expect("outside dir");
+    disclose_cmd()
+        .current_dir(&outside)
+        .arg("status")
+        .assert()
+        .failure()
+        .code(1);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_layered_config() {
+    let temp = TempDir::new().expect("tempdir");
+    let user_dir = temp.path().join("user-config");
+    let workspace = temp.path().join("configured");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let cmd = || {
+        let mut cmd = disclose_cmd();
+        cmd.env("DISCLOSE_CONFIG_DIR", &user_dir)
+            .env_remove("DISCLOSE_EXPORT_FORMAT");
+        cmd
+    };
+
+    cmd()
+        .args(["config", "set", "--user", "init.template", "code"])
+        .assert()
+        .success();
+    cmd()
+        .args(["config", "set", "--user", "init.author", "Ada"])
+        .assert()
+        .success();
+    cmd()
+        .args(["init", "--title", "Configured", "--out", workspace_str])
+        .assert()
+        .success();
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["template"]["slug"], "code");
+    assert_eq!(manifest["project"]["author"], "Ada");
+
+    cmd()
+        .args([
+            "config",
+            "set",
+            "--path",
+            workspace_str,
+            "export.format",
+            "dir",
+        ])
+        .assert()
+        .success();
+    let bundle = temp.path().join("bundle-dir");
+    cmd()
+        .args(["config", "set", "--path", workspace_str, "export.bundle"])
+        .arg(&bundle)
+        .assert()
+        .success();
+    assert!(workspace.join(".disclose/config.toml").is_file());
+    cmd()
+        .args(["export", "--path", workspace_str])
+        .assert()
+        .success();
+    assert!(bundle.join("disclosure.json").is_file());
+
+    let output = cmd()
+        .env("DISCLOSE_EXPORT_FORMAT", "zip")
+        .args([
+            "--json",
+            "config",
+            "get",
+            "export.format",
+            "--path",
+            workspace_str,
+        ])
+        .output()
+        .expect("config get");
+    let value: Value = serde_json::from_slice(&output.stdout).expect("config json");
+    assert_eq!(value["result"]["entry"]["value"], "zip");
+    assert_eq!(value["result"]["entry"]["source"]["kind"], "env");
+    assert_eq!(
+        value["result"]["entry"]["source"]["var"],
+        "DISCLOSE_EXPORT_FORMAT"
+    );
+
+    let output = cmd()
+        .args(["--json", "config", "list", "--path", workspace_str])
+        .output()
+        .expect("config list");
+    let listing: Value = serde_json::from_slice(&output.stdout).expect("list json");
+    let values = listing["result"]["values"].as_array().expect("values");
+    let source = |key: &str| {
+        values
+            .iter()
+            .find(|entry| entry["key"] == key)
+            .map(|entry| entry["source"]["kind"].clone())
+    };
+    assert_eq!(source("init.template"), Some(Value::from("user")));
+    assert_eq!(source("export.format"), Some(Value::from("workspace")));
+
+    cmd()
+        .args([
+            "config",
+            "set",
+            "--path",
+            workspace_str,
+            "export.format",
+            "tar",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+    cmd()
+        .args([
+            "config",
+            "set",
+            "--path",
+            workspace_str,
+            "export.formt",
+            "zip",
+        ])
+        .assert()
+        .failure()
+        .code(2);
+    cmd()
+        .args(["config", "unset", "--path", workspace_str, "export.format"])
+        .assert()
+        .success();
+    cmd()
+        .args(["config", "get", "export.format", "--path", workspace_str])
+        .assert()
+        .failure()
+        .code(1);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_login_logout() {
+    let temp = TempDir::new().expect("tempdir");
+    let user_dir = temp.path().join("user-config");
+    let workspace = init_workspace(&temp, "login");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let cmd = || {
+        let mut cmd = disclose_cmd();
+        cmd.env("DISCLOSE_CONFIG_DIR", &user_dir)
+            .env_remove("DISCLOSE_TOKEN")
+            .env_remove("DISCLOSE_PUBLISH_ENDPOINT");
+        cmd
+    };
+
+    let (endpoint, rx, handle) = spawn_publish_server();
+    cmd()
+        .args(["login", "--endpoint", &format!("{}/", endpoint)])
+        .write_stdin("secret-token\n")
+        .assert()
+        .success();
+    let credentials = user_dir.join("credentials.json");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&credentials)
+            .expect("credentials")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let stored = read_json(&credentials);
+    assert_eq!(stored["tokens"][endpoint.as_str()]["token"], "secret-token");
+
+    cmd()
+        .args(["publish", "--path", workspace_str, "--endpoint", &endpoint])
+        .assert()
+        .success();
+    let (_, authorization) = rx
+        .recv_timeout(Duration::from_secs(5))
+        .expect("publish request");
+    assert_eq!(authorization.as_deref(), Some("Bearer secret-token"));
+    handle.join().expect("server thread");
+
+    let output = cmd()
+        .args(["--json", "logout", "--endpoint", &endpoint])
+        .output()
+        .expect("logout");
+    assert!(output.status.success());
+    let value: Value = serde_json::from_slice(&output.stdout).expect("logout json");
+    assert_eq!(value["result"]["removed"][0], endpoint.as_str());
+    let stored = read_json(&credentials);
+    assert!(stored["tokens"].as_object().expect("tokens").is_empty());
+
+    cmd()
+        .args(["login", "--endpoint", "disclose.example.com"])
+        .write_stdin("secret-token\n")
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_republish_and_unpublish() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "republish");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let cmd = || {
+        let mut cmd = disclose_cmd();
+        cmd.env("DISCLOSE_CONFIG_DIR", temp.path().join("user-config"))
+            .env("DISCLOSE_TOKEN", "owner-token")
+            .env_remove("DISCLOSE_PUBLISH_ENDPOINT");
+        cmd
+    };
+    let (endpoint, rx, handle) = spawn_api_server(4);
+    let next = || rx.recv_timeout(Duration::from_secs(5)).expect("request");
+    let publish = |extra: &[&str]| {
+        let output = cmd()
+            .args(["--json", "publish", "--path", workspace_str, "--endpoint"])
+            .arg(&endpoint)
+            .args(extra)
+            .output()
+            .expect("publish");
+        assert!(output.status.success(), "{:?}", output);
+        let value: Value = serde_json::from_slice(&output.stdout).expect("publish json");
+        value["result"].clone()
+    };
+
+    let first = publish(&[]);
+    assert_eq!(next().0, "POST");
+    assert_eq!(first["updated"], false);
+
+    let update = publish(&[]);
+    let (method, path, authorization) = next();
+    assert_eq!(method, "PUT");
+    assert_eq!(path, "/api/disclosures/slug-1");
+    assert_eq!(authorization.as_deref(), Some("Bearer owner-token"));
+    assert_eq!(update["updated"], true);
+    assert_eq!(update["revision"], 2);
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["publication"]["slug"], "slug-1");
+    assert_eq!(manifest["publication"]["revision"], 2);
+    assert!(manifest["publication"]["updated_at"].is_string());
+
+    let fork = publish(&["--new"]);
+    assert_eq!(next().0, "POST");
+    assert_eq!(fork["forked_from"], "slug-1");
+
+    cmd()
+        .args(["unpublish", "--path", workspace_str, "--endpoint"])
+        .arg(&endpoint)
+        .assert()
+        .success();
+    let (method, path, _) = next();
+    assert_eq!(method, "DELETE");
+    assert_eq!(path, "/api/disclosures/slug-1");
+    handle.join().expect("server thread");
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert!(manifest.get("publication").is_none());
+
+    cmd()
+        .args(["unpublish", "--path", workspace_str, "--endpoint"])
+        .arg(&endpoint)
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_fetch_verifies_publication() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "source");
+    let (proof_a, _) = write_proof_files(&temp);
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            workspace.to_str().expect("workspace str"),
+            "--proof",
+        ])
+        .arg(&proof_a)
+        .assert()
+        .success();
+
+    let mut manifest = read_json(&workspace.join("disclosure.json"));
+    let hashes = read_json(&workspace.join("hashes.json"));
+    manifest["publication"] =
+        serde_json::json!({ "slug": "abc123", "url": "https://example.test/d/abc123" });
+    let served = serde_json::json!({ "manifest": manifest, "hashes": hashes, "receipts": [] });
+    let mut tampered = served.clone();
+    tampered["manifest"]["project"]["title"] = Value::from("Edited on the server");
+
+    let (endpoint, rx, handle) = spawn_get_server(vec![served.clone(), tampered, served]);
+    let out = temp.path().join("fetched");
+    let fetch = || {
+        let mut cmd = disclose_cmd();
+        cmd.args(["--json", "fetch"])
+            .arg(format!("{}/d/abc123", endpoint))
+            .arg("--out")
+            .arg(&out);
+        cmd
+    };
+
+    let output = fetch().output().expect("fetch");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).expect("request"),
+        "/api/disclosures/abc123"
+    );
+    let report: Value = serde_json::from_slice(&output.stdout).expect("fetch json");
+    assert_eq!(report["result"]["ok"], true);
+    assert_eq!(
+        report["result"]["report"]["bundle_root_sha256"],
+        hashes["bundle_root_sha256"]
+    );
+    assert!(fs::metadata(out.join("disclosure.json"))
+        .expect("fetched manifest")
+        .permissions()
+        .readonly());
+
+    disclose_cmd()
+        .args([
+            "attach",
+            "--path",
+            out.to_str().expect("out str"),
+            "--proof",
+        ])
+        .arg(&proof_a)
+        .assert()
+        .failure()
+        .code(2);
+    let status = disclose_cmd()
+        .args(["status", "--path", out.to_str().expect("out str")])
+        .output()
+        .expect("status");
+    assert!(String::from_utf8_lossy(&status.stdout).contains("Read-only copy fetched from"));
+
+    let output = fetch().output().expect("fetch tampered");
+    assert_eq!(output.status.code(), Some(3));
+    let report: Value = serde_json::from_slice(&output.stdout).expect("fetch json");
+    let mismatches = report["result"]["mismatches"]
+        .as_array()
+        .expect("mismatches");
+    assert!(mismatches.contains(&Value::from("manifest_sha256 (hashes.json)")));
+
+    fetch().arg("--quiet").assert().success();
+    handle.join().expect("server thread");
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_publish_dry_run() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "dry-run");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let (proof_a, _) = write_proof_files(&temp);
+    disclose_cmd()
+        .args(["attach", "--path", workspace_str, "--proof"])
+        .arg(&proof_a)
+        .assert()
+        .success();
+    fs::create_dir_all(workspace.join("receipts")).expect("receipts dir");
+    fs::write(workspace.join("receipts/bundle-root.ots"), [0u8; 42]).expect("receipt");
+    // Nothing listens here, so any real request would fail.
+    let endpoint = "http://127.0.0.1:9";
+
+    let output = disclose_cmd()
+        .args([
+            "publish",
+            "--dry-run",
+            "--path",
+            workspace_str,
+            "--endpoint",
+            endpoint,
+        ])
+        .output()
+        .expect("dry run");
+    assert!(output.status.success(), "{:?}", output);
+    let body: Value = serde_json::from_slice(&output.stdout).expect("body is the exact json");
+    assert_eq!(body["manifest"]["project"]["author"], "Test Runner");
+    assert_eq!(body["receipts"][0]["filename"], "bundle-root.ots");
+    let summary = String::from_utf8_lossy(&output.stderr);
+    assert!(summary.contains("POST http://127.0.0.1:9/api/disclosures"));
+    assert!(summary.contains("bundle-root.ots (42 bytes)"));
+    assert!(summary.contains("No personal data or secrets found"));
+
+    let out = temp.path().join("body.json");
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "publish",
+            "--dry-run",
+            "--path",
+            workspace_str,
+            "--endpoint",
+            endpoint,
+        ])
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .expect("dry run json");
+    assert!(output.status.success());
+    let value: Value = serde_json::from_slice(&output.stdout).expect("dry run json");
+    let plan = &value["result"]["plan"];
+    assert_eq!(plan["method"], "POST");
+    assert_eq!(
+        plan["body_bytes"].as_u64(),
+        Some(fs::metadata(&out).expect("body").len())
+    );
+    assert_eq!(read_json(&out), value["result"]["body"]);
+    assert_eq!(plan["findings"], serde_json::json!([]));
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert!(manifest.get("publication").is_none());
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_sensitive_content_blocks() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "sensitive");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let secret = temp.path().join("config.env");
+    fs::write(
+        &secret,
+        "name = demo\nOPENAI_API_KEY=sk-abcdefghijklmnopqrstuvwx\n",
+    )
+    .expect("write secret");
+    disclose_cmd()
+        .args(["attach", "--path", workspace_str, "--proof"])
+        .arg(&secret)
+        .assert()
+        .success();
+
+    // Hashes only: the proof file is not part of the bundle, so nothing to flag.
+    disclose_cmd()
+        .args(["export", "--path", workspace_str, "--format", "dir"])
+        .arg("--bundle")
+        .arg(temp.path().join("clean"))
+        .assert()
+        .success();
+
+    let copies = temp.path().join("copies");
+    let output = disclose_cmd()
+        .args(["export", "--path", workspace_str, "--format", "dir"])
+        .args(["--include-proof", "copies", "--bundle"])
+        .arg(&copies)
+        .output()
+        .expect("export copies");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("API key or secret in"), "{}", stderr);
+    assert!(stderr.contains(":2:"), "{}", stderr);
+    assert!(!stderr.contains("abcdefghijklmnop"), "{}", stderr);
+    assert!(!copies.exists());
+
+    let output = disclose_cmd()
+        .args([
+            "--json",
+            "export",
+            "--path",
+            workspace_str,
+            "--format",
+            "dir",
+        ])
+        .args(["--include-proof", "copies", "--allow-sensitive", "--bundle"])
+        .arg(&copies)
+        .output()
+        .expect("export allowed");
+    assert!(output.status.success(), "{:?}", output);
+    let value: Value = serde_json::from_slice(&output.stdout).expect("export json");
+    assert_eq!(value["result"]["findings"][0]["kind"], "api_key");
+    assert!(copies.join("proof/config.env").exists());
+
+    disclose_cmd()
+        .args(["project", "set", "--path", workspace_str])
+        .args(["--assistance-notes", "Questions to ada@example.com"])
+        .assert()
+        .success();
+    let endpoint = "http://127.0.0.1:9";
+    let output = disclose_cmd()
+        .args(["--json", "publish", "--dry-run", "--path", workspace_str])
+        .args(["--endpoint", endpoint])
+        .output()
+        .expect("dry run");
+    assert!(output.status.success(), "{:?}", output);
+    let value: Value = serde_json::from_slice(&output.stdout).expect("dry run json");
+    let findings = value["result"]["plan"]["findings"]
+        .as_array()
+        .expect("findings");
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0]["kind"], "email");
+    assert_eq!(findings[0]["location"], "disclosure.json: assistance.notes");
+
+    // Blocked before anything is sent; nothing listens on the endpoint.
+    let output = disclose_cmd()
+        .args(["publish", "--path", workspace_str, "--endpoint", endpoint])
+        .output()
+        .expect("publish");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--allow-sensitive"));
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_path_policy_redacts_exports() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "paths");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let (proof_a, _) = write_proof_files(&temp);
+    disclose_cmd()
+        .args(["attach", "--path", workspace_str, "--proof"])
+        .arg(&proof_a)
+        .assert()
+        .success();
+    let output = disclose_cmd()
+        .args(["--json", "project", "set", "--path", workspace_str])
+        .args(["--path-policy", "basename"])
+        .output()
+        .expect("project set");
+    assert!(output.status.success(), "{:?}", output);
+    let report: Value = serde_json::from_slice(&output.stdout).expect("project json");
+    assert_eq!(report["result"]["path_policy"], "basename");
+
+    let local = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(local["proof"]["items"][0]["path"], "../proof-src/notes.txt");
+    assert_eq!(local["proof"]["path_policy"], "basename");
+
+    let bundle = temp.path().join("bundle");
+    disclose_cmd()
+        .args(["export", "--path", workspace_str, "--format", "dir"])
+        .args(["--include-proof", "copies", "--bundle"])
+        .arg(&bundle)
+        .assert()
+        .success();
+    let exported = read_json(&bundle.join("disclosure.json"));
+    assert_eq!(exported["proof"]["items"][0]["path"], "notes.txt");
+    assert_eq!(exported["proof"]["path_policy"], "basename");
+    assert_eq!(
+        exported["proof"]["bundle_root_sha256"],
+        local["proof"]["bundle_root_sha256"]
+    );
+    let hashes = read_json(&bundle.join("hashes.json"));
+    assert_eq!(hashes["proof"][0]["path"], "notes.txt");
+    assert_eq!(
+        hashes["bundle_root_sha256"],
+        local["proof"]["bundle_root_sha256"]
+    );
+    assert!(bundle.join("proof/notes.txt").exists());
+
+    let hashed = temp.path().join("hashed");
+    disclose_cmd()
+        .args(["export", "--path", workspace_str, "--format", "dir"])
+        .args([
+            "--include-proof",
+            "copies",
+            "--path-policy",
+            "hash",
+            "--bundle",
+        ])
+        .arg(&hashed)
+        .assert()
+        .success();
+    let exported = read_json(&hashed.join("disclosure.json"));
+    let path = exported["proof"]["items"][0]["path"]
+        .as_str()
+        .expect("hashed path");
+    let hex = path.strip_prefix("sha256:").expect("sha256 prefix");
+    assert!(is_hex(hex, 64));
+    assert!(hashed.join("proof").join(hex).exists());
+    assert_ne!(
+        exported["proof"]["bundle_root_sha256"],
+        local["proof"]["bundle_root_sha256"]
+    );
+
+    let output = disclose_cmd()
+        .args(["--json", "publish", "--dry-run", "--path", workspace_str])
+        .args(["--endpoint", "http://127.0.0.1:9"])
+        .output()
+        .expect("dry run");
+    assert!(output.status.success(), "{:?}", output);
+    let value: Value = serde_json::from_slice(&output.stdout).expect("dry run json");
+    assert_eq!(
+        value["result"]["body"]["manifest"]["proof"]["items"][0]["path"],
+        "notes.txt"
+    );
+
+    // A receipt pins the policy: switching it would change the bundle root.
+    let mut stamped = local.clone();
+    stamped["timestamps"] = serde_json::json!({
+        "opentimestamps": { "receipt_sha256": "ab".repeat(32), "receipt_filename": "bundle-root.ots" }
+    });
+    fs::write(
+        workspace.join("disclosure.json"),
+        serde_json::to_string_pretty(&stamped).expect("manifest"),
+    )
+    .expect("write manifest");
+    disclose_cmd()
+        .args(["export", "--path", workspace_str, "--format", "dir"])
+        .args(["--path-policy", "relative", "--bundle"])
+        .arg(temp.path().join("refused"))
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_publish_retries_and_server_errors() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "retries");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let publish = |endpoint: &str, extra: &[&str]| {
+        disclose_cmd()
+            .args(["--json", "publish", "--path", workspace_str])
+            .args(["--endpoint", endpoint, "--include-receipts", "no"])
+            .args(extra)
+            .output()
+            .expect("publish")
+    };
+    let error = |output: &std::process::Output| -> Value {
+        let value: Value = serde_json::from_slice(&output.stdout).expect("error json");
+        assert_eq!(value["ok"], false);
+        value["error"].clone()
+    };
+
+    // A POST that failed with a 5xx may still have created a publication,
+    // so it is not retried.
+    let (endpoint, rx, handle) = spawn_status_server(vec![(
+        503,
+        serde_json::json!({ "ok": false, "error": "Database unavailable" }),
+    )]);
+    let output = publish(&endpoint, &["--retries", "1"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(error(&output)["details"]["attempts"], 1);
+    handle.join().expect("server");
+    assert_eq!(rx.try_iter().count(), 1);
+
+    let (endpoint, _rx, handle) = spawn_status_server(vec![(
+        200,
+        serde_json::json!({ "slug": "slug-1", "url": "http://example.test/d/slug-1", "revision": 1 }),
+    )]);
+    let output = publish(&endpoint, &[]);
+    assert!(output.status.success(), "{:?}", output);
+    handle.join().expect("server");
+
+    // Updates are PUTs: a 503 is retried and the second attempt succeeds.
+    let (endpoint, rx, handle) = spawn_status_server(vec![
+        (
+            503,
+            serde_json::json!({ "ok": false, "error": "Database unavailable" }),
+        ),
+        (
+            200,
+            serde_json::json!({ "slug": "slug-1", "url": "http://example.test/d/slug-1", "revision": 2 }),
+        ),
+    ]);
+    let output = publish(&endpoint, &["--token", "t", "--retries", "1"]);
+    assert!(output.status.success(), "{:?}", output);
+    handle.join().expect("server");
+    let paths: Vec<String> = rx.try_iter().collect();
+    assert_eq!(paths, vec!["/api/disclosures/slug-1"; 2]);
+
+    // Validation errors from the server come back typed, without retries.
+    let (endpoint, rx, handle) = spawn_status_server(vec![(
+        400,
+        serde_json::json!({ "ok": false, "errors": ["/project: must have required property 'title'"] }),
+    )]);
+    let output = publish(&endpoint, &["--new"]);
+    assert_eq!(output.status.code(), Some(2));
+    let err = error(&output);
+    assert_eq!(err["kind"], "rejected");
+    assert_eq!(err["exit_code"], 2);
+    assert_eq!(err["details"]["status"], 400);
+    assert_eq!(
+        err["details"]["errors"][0],
+        "/project: must have required property 'title'"
+    );
+    handle.join().expect("server");
+    assert_eq!(rx.try_iter().count(), 1);
+
+    let (endpoint, _rx, handle) = spawn_status_server(vec![(
+        401,
+        serde_json::json!({ "ok": false, "error": "Unauthorized" }),
+    )]);
+    let output = publish(&endpoint, &["--new", "--token", "wrong"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert_eq!(error(&output)["kind"], "unauthorized");
+    handle.join().expect("server");
+
+    let (endpoint, _rx, handle) = spawn_status_server(vec![
+        (500, serde_json::json!({ "ok": false, "error": "boom" })),
+        (502, serde_json::json!({ "ok": false, "error": "boom" })),
+    ]);
+    let output = publish(&endpoint, &["--token", "t", "--retries", "1"]);
+    assert_eq!(output.status.code(), Some(4));
+    let err = error(&output);
+    assert_eq!(err["kind"], "server");
+    assert_eq!(err["details"]["status"], 502);
+    assert_eq!(err["details"]["attempts"], 2);
+    handle.join().expect("server");
+
+    // Nothing listens here.
+    let output = publish("http://127.0.0.1:9", &["--new", "--retries", "0"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(error(&output)["kind"], "unreachable");
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["publication"]["slug"], "slug-1");
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_publish_static_site() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "static");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let (proof_a, _) = write_proof_files(&temp);
+    disclose_cmd()
+        .args(["attach", "--path", workspace_str, "--proof"])
+        .arg(&proof_a)
+        .assert()
+        .success();
+    let site = temp.path().join("site");
+    // The target comes from config (here its environment variable).
+    let publish = |extra: &[&str]| {
+        let output = disclose_cmd()
+            .env("DISCLOSE_PUBLISH_TARGET", "static")
+            .args(["--json", "publish", "--path", workspace_str])
+            .args([
+                "--include-receipts",
+                "no",
+                "--base-url",
+                "https://proofs.example",
+            ])
+            .arg("--site-dir")
+            .arg(&site)
+            .args(extra)
+            .output()
+            .expect("publish");
+        assert!(output.status.success(), "{:?}", output);
+        serde_json::from_slice::<Value>(&output.stdout).expect("publish json")["result"].clone()
+    };
+
+    let plan = publish(&["--dry-run"]);
+    assert_eq!(plan["plan"]["target"], "static");
+    assert_eq!(plan["plan"]["method"], "WRITE");
+    assert!(!site.exists());
+
+    let report = publish(&[]);
+    assert_eq!(report["target"], "static");
+    assert_eq!(report["revision"], 1);
+    let slug = report["slug"].as_str().expect("slug").to_string();
+    assert_eq!(
+        report["url"],
+        format!("https://proofs.example/d/{}/", slug).as_str()
+    );
+
+    let page_dir = site.join("d").join(&slug);
+    let page = fs::read_to_string(page_dir.join("index.html")).expect("index.html");
+    let hashes = read_json(&page_dir.join("hashes.json"));
+    assert!(page.contains("<h1>CLI E2E Disclosure</h1>"));
+    assert!(page.contains(hashes["manifest_sha256"].as_str().expect("manifest sha")));
+    assert!(page.contains(hashes["proof"][0]["sha256"].as_str().expect("proof sha")));
+    let published = read_json(&page_dir.join("disclosure.json"));
+    assert_eq!(published["publication"]["slug"], slug.as_str());
+    assert_eq!(published["publication"]["revision"], 1);
+    let document = read_json(&site.join("api/disclosures").join(&slug));
+    assert_eq!(document["manifest"], published);
+    assert_eq!(document["hashes"], hashes);
+
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["publication"]["target"], "static");
+
+    // The served site answers `disclose fetch` like the web API.
+    let (endpoint, rx, handle) = spawn_get_server(vec![document]);
+    let out = temp.path().join("fetched");
+    let output = disclose_cmd()
+        .args(["--json", "fetch"])
+        .arg(format!("{}/d/{}", endpoint, slug))
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .expect("fetch");
+    assert!(output.status.success(), "{:?}", output);
+    handle.join().expect("server");
+    assert_eq!(
+        rx.recv().expect("request"),
+        format!("/api/disclosures/{}", slug)
+    );
+
+    let report = publish(&[]);
+    assert_eq!(report["slug"], slug.as_str());
+    assert_eq!(report["updated"], true);
+    assert_eq!(report["revision"], 2);
+    let published = read_json(&page_dir.join("disclosure.json"));
+    assert_eq!(published["publication"]["revision"], 2);
+    assert!(published["publication"]["updated_at"].is_string());
+
+    // Removing it needs the same target.
+    let output = disclose_cmd()
+        .args(["unpublish", "--path", workspace_str])
+        .args(["--endpoint", "http://127.0.0.1:9"])
+        .output()
+        .expect("unpublish");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--target static"));
+
+    disclose_cmd()
+        .args(["unpublish", "--path", workspace_str, "--target", "static"])
+        .arg("--site-dir")
+        .arg(&site)
+        .assert()
+        .success();
+    assert!(!page_dir.exists());
+    assert!(!site.join("api/disclosures").join(&slug).exists());
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert!(manifest.get("publication").is_none());
+}
+
+type S3Request = (String, String, Option<String>);
+type S3Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// A MinIO-style stand-in: stores PUT bodies by path, serves them on GET,
+/// deletes them on DELETE and reports every request.
+fn spawn_s3_server() -> (String, mpsc::Receiver<S3Request>, S3Objects) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind server");
+    let endpoint = format!("http://{}", listener.local_addr().expect("local addr"));
+    let objects = Arc::new(Mutex::new(HashMap::new()));
+    let store = Arc::clone(&objects);
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.expect("accept");
+            let (request_line, authorization, body) = read_http_request(&mut stream);
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or("").to_string();
+            let path = parts.next().unwrap_or("").to_string();
+            let (status, reply) = {
+                let mut objects = store.lock().expect("objects");
+                match method.as_str() {
+                    "PUT" => {
+                        objects.insert(path.clone(), body);
+                        (200, Vec::new())
+                    }
+                    "GET" => match objects.get(&path) {
+                        Some(bytes) => (200, bytes.clone()),
+                        None => (
+                            404,
+                            b"<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>"
+                                .to_vec(),
+                        ),
+                    },
+                    "DELETE" => {
+                        objects.remove(&path);
+                        (204, Vec::new())
+                    }
+                    _ => (405, Vec::new()),
+                }
+            };
+            let head = format!(
+                "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                reply.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(&reply);
+            let _ = tx.send((method, path, authorization));
+        }
+    });
+
+    (endpoint, rx, objects)
+}
+
+#[test]
+#[ignore]
+fn cli_e2e_publish_s3_bucket() {
+    let temp = TempDir::new().expect("tempdir");
+    let workspace = init_workspace(&temp, "s3");
+    let workspace_str = workspace.to_str().expect("workspace str");
+    let (endpoint, rx, objects) = spawn_s3_server();
+    let s3_cmd = |args: &[&str]| {
+        let mut cmd = disclose_cmd();
+        cmd.env("DISCLOSE_PUBLISH_S3_ENDPOINT", &endpoint)
+            .env("DISCLOSE_PUBLISH_S3_PREFIX", "site")
+            .env("AWS_ACCESS_KEY_ID", "TESTACCESSKEY")
+            .env("AWS_SECRET_ACCESS_KEY", "test-secret")
+            .env_remove("AWS_SESSION_TOKEN")
+            .args(args)
+            .args([
+                "--path",
+                workspace_str,
+                "--target",
+                "s3",
+                "--bucket",
+                "proofs",
+            ]);
+        cmd
+    };
+    let publish = || {
+        let output = s3_cmd(&["--json", "publish", "--include-receipts", "no"])
+            .output()
+            .expect("publish");
+        assert!(output.status.success(), "{:?}", output);
+        serde_json::from_slice::<Value>(&output.stdout).expect("publish json")["result"].clone()
+    };
+
+    // Keys come from the environment only.
+    let output = s3_cmd(&["publish", "--include-receipts", "no"])
+        .env_remove("AWS_SECRET_ACCESS_KEY")
+        .output()
+        .expect("publish");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("AWS_SECRET_ACCESS_KEY"));
+    assert!(objects.lock().expect("objects").is_empty());
+
+    let report = publish();
+    let slug = report["slug"].as_str().expect("slug").to_string();
+    assert_eq!(report["target"], "s3");
+    assert_eq!(report["revision"], 1);
+    assert_eq!(
+        report["url"],
+        format!("{}/proofs/site/d/{}/index.html", endpoint, slug).as_str()
+    );
+    let requests: Vec<S3Request> = rx.try_iter().collect();
+    // A lookup for an existing publication, then the uploads.
+    assert_eq!(requests[0].0, "GET");
+    assert!(requests
+        .iter()
+        .skip(1)
+        .all(|(method, _, _)| method == "PUT"));
+    assert!(requests.iter().all(|(_, _, authorization)| {
+        authorization.as_deref().is_some_and(|value| {
+            value.starts_with("AWS4-HMAC-SHA256 Credential=TESTACCESSKEY/")
+                && value.contains("/us-east-1/s3/aws4_request")
+        })
+    }));
+    {
+        let objects = objects.lock().expect("objects");
+        let page = &objects[&format!("/proofs/site/d/{}/index.html", slug)];
+        assert!(String::from_utf8_lossy(page).contains("CLI E2E Disclosure"));
+        let document: Value =
+            serde_json::from_slice(&objects[&format!("/proofs/site/api/disclosures/{}", slug)])
+                .expect("api document");
+        assert_eq!(document["manifest"]["publication"]["revision"], 1);
+        assert!(objects.contains_key(&format!("/proofs/site/d/{}/hashes.json", slug)));
+    }
+
+    let report = publish();
+    assert_eq!(report["slug"], slug.as_str());
+    assert_eq!(report["revision"], 2);
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert_eq!(manifest["publication"]["target"], "s3");
+    assert_eq!(manifest["publication"]["revision"], 2);
+
+    s3_cmd(&["unpublish"]).assert().success();
+    assert!(objects.lock().expect("objects").is_empty());
+    let manifest = read_json(&workspace.join("disclosure.json"));
+    assert!(manifest.get("publication").is_none());
+}